@@ -0,0 +1,20 @@
+use anyhow::Result;
+
+use idle_termquest::sim::{self, Setup};
+
+// runs battles without the tui, for balancing the data files
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        println!("{}", sim::USAGE);
+        return Ok(());
+    }
+    let setup = Setup::parse(args)?;
+    let report = sim::run(&setup)?;
+    if setup.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report);
+    }
+    Ok(())
+}
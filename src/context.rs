@@ -1,10 +1,23 @@
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Deserializer};
+
+use crate::core::Catalog;
 
 #[derive(Default)]
 pub struct Context {
     pub config: Config,
     pub should_quit: bool,
     pub should_suspend: bool,
+    pub catalog: Rc<Catalog>,
     log: VecDeque<String>,
 }
 
@@ -18,6 +31,11 @@ impl Context {
         self
     }
 
+    pub fn catalog(mut self, catalog: Catalog) -> Self {
+        self.catalog = Rc::new(catalog);
+        self
+    }
+
     pub fn push_log(&mut self, text_log: String) {
         self.log.push_back(text_log);
         if self.log.len() > 3 {
@@ -30,8 +48,77 @@ impl Context {
     }
 }
 
-#[derive(Default)]
+// read from config.json in the data dir, anything left out keeps its default
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
     pub tick_rate: f64,
     pub frame_rate: f64,
+    // how many commands the history keeps
+    pub history_len: usize,
+    // how long a hero waits for orders in manual mode before acting on its own
+    #[serde(deserialize_with = "secs")]
+    pub manual_timeout: Duration,
+    // breather between the end of a battle and the next wave
+    #[serde(deserialize_with = "secs")]
+    pub battle_pause: Duration,
+    // from --seed, starts a new game that plays out the same every time
+    #[serde(skip)]
+    pub seed: Option<u64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tick_rate: 24.,
+            frame_rate: 24.,
+            history_len: 500,
+            manual_timeout: Duration::from_secs(15),
+            battle_pause: Duration::from_secs(3),
+            seed: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn file() -> PathBuf {
+        crate::core::data_dir().join("config.json")
+    }
+
+    // no file is fine, a broken one is reported before the game starts
+    pub fn load_from(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("cannot parse {}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("cannot read {}", path.display())),
+        }
+    }
+}
+
+// timings are written as seconds, fractions allowed
+fn secs<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let secs = f64::deserialize(deserializer)?;
+    Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+use crate::testing::TestDir;
+
+#[test]
+fn test_config_file() {
+    let dir = TestDir::new("config");
+    let path = dir.join("config.json");
+    let load = |content: &str| {
+        fs::write(&path, content).unwrap();
+        Config::load_from(&path)
+    };
+    assert_eq!(Config::load_from(&dir.join("missing.json")).unwrap(), Config::default());
+    let config = load(r#"{ "battle_pause": 0.5, "tick_rate": 30, "history_len": 50 }"#).unwrap();
+    assert_eq!(config.battle_pause, Duration::from_millis(500));
+    assert_eq!(config.tick_rate, 30.);
+    assert_eq!(config.history_len, 50);
+    assert_eq!(config.manual_timeout, Config::default().manual_timeout);
+    assert!(load(r#"{ "battle_pause": -1 }"#).is_err());
+    assert!(load(r#"{ "pause": 1 }"#).is_err());
 }
@@ -0,0 +1,714 @@
+use std::{cell::Ref, fmt::Display};
+
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    entities::{Entity, Id, SharedEntity},
+    types::{BasicStat, Element, Target},
+    world::World,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DamageKind {
+    Physical,
+    Magical,
+}
+
+impl Display for DamageKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DamageKind::Physical => write!(f, "physical"),
+            DamageKind::Magical => write!(f, "magical"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Attack { target: Id, kind: DamageKind },
+    // index into the actor's skills, the target only matters for single target skills
+    Skill { skill: usize, target: Id },
+    Wait,
+}
+
+#[derive(Debug)]
+pub struct Outcome {
+    pub actor: Box<str>,
+    pub target: Box<str>,
+    pub kind: DamageKind,
+    pub element: Option<Element>,
+    pub damage: u32,
+    pub absorbed: u32,
+    pub killed: bool,
+    pub missed: bool,
+    // crits and weaknesses, see notes
+    pub notes: Vec<String>,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.missed {
+            return write!(f, "{} attacks {}: MISS", self.actor, self.target);
+        }
+        let kind = match self.element {
+            Some(element) => element.to_string(),
+            None => self.kind.to_string(),
+        };
+        write!(
+            f,
+            "{} hits {} for {} {} damage",
+            self.actor, self.target, self.damage, kind
+        )?;
+        let mut notes = self.notes.clone();
+        if self.absorbed > 0 {
+            notes.push(format!("{} absorbed", self.absorbed));
+        }
+        if !notes.is_empty() {
+            write!(f, " ({})", notes.join(", "))?;
+        }
+        if self.killed {
+            write!(f, ", {} is defeated", self.target)?;
+        }
+        Ok(())
+    }
+}
+
+// att^2 / (att + def): equal att and def halves the hit, defense never fully negates it
+pub fn damage(attacker: &BasicStat, defender: &BasicStat, kind: DamageKind) -> u32 {
+    let (att, def) = match kind {
+        DamageKind::Physical => (attacker.p_att, defender.p_def),
+        DamageKind::Magical => (attacker.m_att, defender.m_def),
+    };
+    let att = att.max(0) as u64;
+    let def = def.max(0) as u64;
+    if att == 0 {
+        return 0;
+    }
+    (att * att / (att + def)).clamp(1, u32::MAX as u64) as u32
+}
+
+// however evasive the target, there is always some chance to land a hit
+const MIN_HIT: i32 = 5;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Hit {
+    pub missed: bool,
+    pub crit: bool,
+}
+
+// accuracy against evasion first, only hits can crit
+pub fn roll(attacker: &BasicStat, defender: &BasicStat, rng: &mut dyn RngCore) -> Hit {
+    let chance = (attacker.accuracy - defender.evasion).clamp(MIN_HIT, 100);
+    if rng.gen_range(0..100) >= chance {
+        return Hit {
+            missed: true,
+            crit: false,
+        };
+    }
+    Hit {
+        missed: false,
+        crit: rng.gen_range(0..100) < attacker.crit_chance,
+    }
+}
+
+// the formula above, crits add crit_damage on top and the defender's resistance scales the rest
+pub fn hit_damage(
+    attacker: &BasicStat,
+    defender: &BasicStat,
+    kind: DamageKind,
+    element: Option<Element>,
+    hit: Hit,
+) -> u32 {
+    if hit.missed {
+        return 0;
+    }
+    let mut damage = damage(attacker, defender, kind);
+    if hit.crit {
+        damage = (damage as u64 * (100 + attacker.crit_damage.max(0)) as u64 / 100) as u32;
+    }
+    match element {
+        Some(element) => defender.resistances.scale(damage, element),
+        None => damage,
+    }
+}
+
+// what the log adds to a hit, like "CRIT!" or "weak to fire"
+pub fn notes(defender: &BasicStat, element: Option<Element>, hit: Hit) -> Vec<String> {
+    let mut notes = Vec::new();
+    if hit.crit {
+        notes.push("CRIT!".to_owned());
+    }
+    notes.extend(element.and_then(|e| defender.resistances.describe(e)));
+    notes
+}
+
+// each entity brings its own policy, see entities::policy
+pub fn choose_action(
+    actor: &dyn Entity,
+    allies: &[&dyn Entity],
+    opponents: &[&dyn Entity],
+    rng: &mut dyn RngCore,
+) -> Action {
+    actor.strategy().policy().choose(actor, allies, opponents, rng)
+}
+
+// the usual Entity::act, the policy picks from whoever is on each side
+pub fn act(actor: &dyn Entity, world: &World) -> Action {
+    let (allies, opponents) = world.sides(actor.get_type());
+    if opponents.is_empty() {
+        return Action::Wait;
+    }
+    let allies: Vec<Ref<dyn Entity>> = allies.iter().map(|e| e.borrow()).collect();
+    let allies: Vec<&dyn Entity> = allies.iter().map(|e| &**e).collect();
+    let opponents: Vec<Ref<dyn Entity>> = opponents.iter().map(|e| e.borrow()).collect();
+    let opponents: Vec<&dyn Entity> = opponents.iter().map(|e| &**e).collect();
+    choose_action(actor, &allies, &opponents, &mut *world.rng())
+}
+
+pub fn attack(actor: &dyn Entity, target: &dyn Entity) -> Action {
+    let (att, def) = (actor.effective_stat(), target.effective_stat());
+    let physical = damage(&att, &def, DamageKind::Physical);
+    let magical = damage(&att, &def, DamageKind::Magical);
+    Action::Attack {
+        target: target.id(),
+        kind: if magical > physical {
+            DamageKind::Magical
+        } else {
+            DamageKind::Physical
+        },
+    }
+}
+
+pub fn resolve(
+    actor: &dyn Entity,
+    action: Action,
+    target: &mut dyn Entity,
+    rng: &mut dyn RngCore,
+) -> Option<Outcome> {
+    let Action::Attack { target: id, kind } = action else {
+        return None;
+    };
+    if id != target.id() || !actor.is_alive() || !target.is_alive() {
+        return None;
+    }
+    let (att, def) = (actor.effective_stat(), target.effective_stat());
+    let element = actor.attack_element();
+    let hit = roll(&att, &def, rng);
+    let raw = hit_damage(&att, &def, kind, element, hit);
+    let damage = target.take_damage(raw);
+    let killed = !target.is_alive();
+    Some(Outcome {
+        actor: actor.info().name.clone(),
+        target: target.info().name.clone(),
+        kind,
+        element,
+        damage,
+        absorbed: raw - damage,
+        killed,
+        missed: hit.missed,
+        notes: notes(&def, element, hit),
+    })
+}
+
+// also where cooldowns tick down and a bit of mana comes back
+pub fn start_turn(actor: &mut dyn Entity) -> Vec<String> {
+    let was_alive = actor.is_alive();
+    for slot in actor.skills_mut() {
+        slot.ready_in = slot.ready_in.saturating_sub(1);
+    }
+    let (stat, status) = actor.stat_and_status_mut();
+    if stat.max_mp > 0 {
+        stat.curr_mp = (stat.curr_mp + (stat.max_mp / 10).max(1)).min(stat.max_mp);
+    }
+    let messages = status.turn_start(stat);
+    named(actor, messages, was_alive)
+}
+
+pub fn end_turn(actor: &mut dyn Entity) -> Vec<String> {
+    let was_alive = actor.is_alive();
+    let (stat, status) = actor.stat_and_status_mut();
+    let messages = status.turn_end(stat);
+    named(actor, messages, was_alive)
+}
+
+// focus overrides the usual target pick when it names a living opponent of the actor
+pub fn take_turn(id: Id, world: &World, focus: Option<Id>) -> Vec<String> {
+    play_turn(id, world, |actor, allies, opponents| {
+        let focused: Vec<&dyn Entity> = opponents
+            .iter()
+            .copied()
+            .filter(|e| Some(e.id()) == focus && e.is_alive())
+            .collect();
+        if focused.is_empty() {
+            actor.act(world)
+        } else {
+            choose_action(actor, allies, &focused, &mut *world.rng())
+        }
+    })
+}
+
+// the player picked the action, it is dropped if the actor can't move this turn
+pub fn take_ordered_turn(id: Id, world: &World, action: Action) -> Vec<String> {
+    play_turn(id, world, |_, _, _| action)
+}
+
+fn play_turn(
+    id: Id,
+    world: &World,
+    decide: impl FnOnce(&dyn Entity, &[&dyn Entity], &[&dyn Entity]) -> Action,
+) -> Vec<String> {
+    let Some(actor) = world.get(id) else {
+        return Vec::new();
+    };
+    let (heroes, enemies) = (world.heroes_dyn(), world.enemies_dyn());
+    let (allies, opponents) = world.sides(actor.borrow().get_type());
+    let (allies, opponents) = (&allies, &opponents);
+
+    let alive_enemies: Vec<Id> = enemies
+        .iter()
+        .map(|e| e.borrow())
+        .filter(|e| e.is_alive())
+        .map(|e| e.id())
+        .collect();
+
+    let mut lines = start_turn(&mut *actor.borrow_mut());
+    if actor.borrow().status().can_act() {
+        let action = {
+            let allies: Vec<Ref<dyn Entity>> = allies.iter().map(|e| e.borrow()).collect();
+            let allies: Vec<&dyn Entity> = allies.iter().map(|e| &**e).collect();
+            let opponents: Vec<Ref<dyn Entity>> = opponents.iter().map(|e| e.borrow()).collect();
+            let opponents: Vec<&dyn Entity> = opponents.iter().map(|e| &**e).collect();
+            decide(&*actor.borrow(), &allies, &opponents)
+        };
+        match action {
+            Action::Attack { target, .. } => {
+                if let Some(target) = opponents.iter().find(|e| e.borrow().id() == target) {
+                    lines.extend(
+                        resolve(&*actor.borrow(), action, &mut *target.borrow_mut(), &mut *world.rng())
+                            .map(|outcome| outcome.to_string()),
+                    );
+                }
+            }
+            Action::Skill { skill, target } => {
+                lines.extend(use_skill(&actor, skill, target, allies, opponents, &mut *world.rng()));
+            }
+            Action::Wait => (),
+        }
+    } else if actor.borrow().is_alive() {
+        lines.push(format!("{} can't move", actor.borrow().info().name));
+    }
+    lines.extend(end_turn(&mut *actor.borrow_mut()));
+
+    // whatever killed them, poison included, the heroes get the xp
+    let xp: u64 = enemies
+        .iter()
+        .map(|e| e.borrow())
+        .filter(|e| alive_enemies.contains(&e.id()) && !e.is_alive())
+        .map(|e| e.xp_reward())
+        .sum();
+    if xp > 0 {
+        lines.extend(award_xp(&heroes, xp));
+    }
+    lines
+}
+
+// pays the cost and starts the cooldown, then hits every target the skill reaches
+pub fn use_skill(
+    actor: &SharedEntity,
+    idx: usize,
+    target: Id,
+    allies: &[SharedEntity],
+    opponents: &[SharedEntity],
+    rng: &mut dyn RngCore,
+) -> Vec<String> {
+    let (caster, name, skill) = {
+        let actor = actor.borrow();
+        let Some(slot) = actor.skills().get(idx) else {
+            return Vec::new();
+        };
+        if !actor.is_alive() || !slot.usable_by(actor.stat()) {
+            return Vec::new();
+        }
+        (actor.effective_stat(), actor.info().name.clone(), slot.skill.boxed_clone())
+    };
+    // revives go to the fallen, everything else to the living
+    let living = |side: &[SharedEntity]| -> Vec<SharedEntity> {
+        side.iter()
+            .filter(|e| e.borrow().is_alive() != skill.revives())
+            .cloned()
+            .collect()
+    };
+    let find = |side: &[SharedEntity]| -> Vec<SharedEntity> {
+        side.iter()
+            .filter(|e| e.borrow().id() == target)
+            .cloned()
+            .collect()
+    };
+    let targets = match skill.target() {
+        Target::Enemy => find(opponents),
+        Target::AllEnemies => living(opponents),
+        Target::Ally => find(allies),
+        Target::AllAllies => living(allies),
+        Target::Myself => vec![actor.clone()],
+    };
+    // nothing to use it on, keep the mana and the cooldown
+    if targets.is_empty() {
+        return Vec::new();
+    }
+    {
+        let mut actor = actor.borrow_mut();
+        actor.skills_mut()[idx].ready_in = skill.cooldown();
+        actor.stat_and_status_mut().0.curr_mp -= skill.cost();
+    }
+
+    let mut lines = vec![format!("{} uses {}", name, skill.name())];
+    for target in targets {
+        let mut target = target.borrow_mut();
+        let was_alive = target.is_alive();
+        let (stat, status) = target.stat_and_status_mut();
+        let messages = skill.use_on(&caster, stat, status, rng);
+        lines.extend(named(&*target, messages, was_alive));
+    }
+    lines
+}
+
+pub fn award_xp(heroes: &[SharedEntity], xp: u64) -> Vec<String> {
+    let mut lines = vec![format!("Heroes gain {} xp", xp)];
+    for hero in heroes {
+        if hero.borrow().is_alive() {
+            lines.extend(hero.borrow_mut().gain_xp(xp));
+        }
+    }
+    lines
+}
+
+fn named(entity: &dyn Entity, messages: Vec<String>, was_alive: bool) -> Vec<String> {
+    let name = &entity.info().name;
+    let mut lines: Vec<String> = messages
+        .into_iter()
+        .map(|m| format!("{} {}", name, m))
+        .collect();
+    if was_alive && !entity.is_alive() {
+        lines.push(format!("{} is defeated", name));
+    }
+    lines
+}
+
+#[cfg(test)]
+use super::{
+    entities::{enemy::Enemy, hero::Hero},
+    world::test_rng,
+    types::{
+        effects::{Burn, DragonPoison, Shield, Stun},
+        skills::{Mend, Strike},
+        Gear, SkillSlot, Slot,
+    },
+};
+
+#[test]
+fn test_damage_formula() {
+    let stat = BasicStat::default();
+    assert_eq!(damage(&stat, &stat, DamageKind::Physical), 7);
+    let weak = BasicStat {
+        p_att: 1,
+        ..Default::default()
+    };
+    assert_eq!(damage(&weak, &stat, DamageKind::Physical), 1);
+    let none = BasicStat {
+        m_att: -3,
+        ..Default::default()
+    };
+    assert_eq!(damage(&none, &stat, DamageKind::Magical), 0);
+}
+
+#[test]
+fn test_choose_action_targets_lowest_alive_hp() {
+    let hero = Hero::new();
+    let mut low = Hero::new();
+    low.stat.curr_hp = 3;
+    low.status.set_alive(false);
+    let mut mid = Hero::new();
+    mid.stat.curr_hp = 10;
+    let high = Hero::new();
+    let action = choose_action(&hero, &[], &[&low, &mid, &high], &mut test_rng());
+    assert_eq!(
+        action,
+        Action::Attack {
+            target: mid.id(),
+            kind: DamageKind::Physical
+        }
+    );
+    assert_eq!(choose_action(&hero, &[], &[&low], &mut test_rng()), Action::Wait);
+}
+
+#[test]
+fn test_choose_action_prefers_magic_when_stronger() {
+    let mut hero = Hero::new();
+    hero.stat.m_att = 30;
+    let enemy = Enemy::new();
+    assert_eq!(
+        choose_action(&hero, &[], &[&enemy], &mut test_rng()),
+        Action::Attack {
+            target: enemy.id(),
+            kind: DamageKind::Magical
+        }
+    );
+}
+
+#[test]
+fn test_resolve_kills_target() {
+    let hero = Hero::new();
+    let mut enemy = Enemy::new();
+    let action = choose_action(&hero, &[], &[&enemy], &mut test_rng());
+
+    let outcome = resolve(&hero, action, &mut enemy, &mut test_rng()).unwrap();
+    assert_eq!(outcome.damage, 7);
+    assert!(!outcome.killed);
+    assert_eq!(enemy.stat().curr_hp, 8);
+
+    let outcome = resolve(&hero, action, &mut enemy, &mut test_rng()).unwrap();
+    assert!(!outcome.killed);
+    let outcome = resolve(&hero, action, &mut enemy, &mut test_rng()).unwrap();
+    assert!(outcome.killed);
+    assert_eq!(enemy.stat().curr_hp, 0);
+    assert!(!enemy.is_alive());
+    assert!(resolve(&hero, action, &mut enemy, &mut test_rng()).is_none());
+}
+
+#[test]
+fn test_shield_absorbs_attack() {
+    let hero = Hero::new();
+    let mut enemy = Enemy::new();
+    enemy.apply_effect(Box::new(Shield { amount: 5, duration: 2 }));
+    let action = choose_action(&hero, &[], &[&enemy], &mut test_rng());
+    let outcome = resolve(&hero, action, &mut enemy, &mut test_rng()).unwrap();
+    assert_eq!(outcome.damage, 2);
+    assert_eq!(outcome.absorbed, 5);
+    assert_eq!(enemy.stat().curr_hp, 13);
+}
+
+#[test]
+fn test_turn_hooks_report_with_names() {
+    let mut enemy = Enemy::new();
+    let name = enemy.info().name.clone();
+    enemy.apply_effect(Box::new(Stun { duration: 1 }));
+    enemy.apply_effect(Box::new(DragonPoison { damage: 15, duration: 1 }));
+    assert!(!enemy.status().can_act());
+    let lines = start_turn(&mut enemy);
+    assert_eq!(
+        lines,
+        vec![
+            format!("{} takes 15 poison damage", name),
+            format!("{} is defeated", name)
+        ]
+    );
+}
+
+#[cfg(test)]
+fn test_world(heroes: Vec<Hero>, enemies: Vec<Enemy>) -> World {
+    let mut world = World::default();
+    for hero in heroes {
+        world.add_hero(hero);
+    }
+    world.set_enemies(enemies);
+    world
+}
+
+#[test]
+fn test_take_turn_follows_focus() {
+    let hero = Hero::new();
+    let hero_id = hero.id;
+    let mut weak = Enemy::new();
+    weak.stat_and_status_mut().0.curr_hp = 3;
+    let strong = Enemy::new();
+    let strong_id = strong.id();
+    let world = test_world(vec![hero], vec![weak, strong]);
+    let enemies = world.enemies();
+
+    take_turn(hero_id, &world, Some(strong_id));
+    assert_eq!(enemies[0].borrow().stat().curr_hp, 3);
+    assert_eq!(enemies[1].borrow().stat().curr_hp, 8);
+
+    take_turn(hero_id, &world, None);
+    assert!(!enemies[0].borrow().is_alive());
+}
+
+#[test]
+fn test_kills_award_xp_to_living_heroes() {
+    let hero = Hero::new();
+    let hero_id = hero.id;
+    let mut fallen = Hero::new();
+    fallen.status.set_alive(false);
+    let mut enemy = Enemy::new();
+    enemy.stat_and_status_mut().0.curr_hp = 1;
+    let reward = enemy.xp_reward();
+    let world = test_world(vec![hero, fallen], vec![enemy]);
+    let heroes = world.heroes();
+
+    let lines = take_turn(hero_id, &world, None);
+    assert!(lines.contains(&format!("Heroes gain {} xp", reward)));
+    assert_eq!(heroes[0].borrow().progress().unwrap().xp, reward);
+    assert_eq!(heroes[1].borrow().progress().unwrap().xp, 0);
+}
+
+#[test]
+fn test_skills_cost_mana_and_cool_down() {
+    let mut hero = Hero::new();
+    let hero_id = hero.id;
+    hero.stat.curr_mp = 5;
+    hero.stat.max_mp = 10;
+    hero.skills = vec![
+        SkillSlot::new(Box::new(Strike {
+            name: "Cleave".to_owned(),
+            kind: DamageKind::Physical,
+            element: None,
+            power: 1.,
+            target: Target::AllEnemies,
+            cost: 5,
+            cooldown: 2,
+            effect: None,
+        })),
+        SkillSlot::new(Box::new(Mend {
+            name: "Heal".to_owned(),
+            power: 1.,
+            target: Target::Ally,
+            cost: 0,
+            cooldown: 1,
+            effect: None,
+        })),
+    ];
+    let mut hurt = Hero::new();
+    hurt.stat.curr_hp = 5;
+    let world = test_world(vec![hero, hurt], vec![Enemy::new(), Enemy::new()]);
+    let (heroes, enemies) = (world.heroes(), world.enemies());
+
+    // healing the hurt ally comes first
+    let lines = take_turn(hero_id, &world, None);
+    assert!(lines[0].ends_with("uses Heal"));
+    assert_eq!(heroes[1].borrow().stat().curr_hp, 15);
+
+    // a point of mana comes back every turn start, cleave hits everyone
+    let lines = take_turn(hero_id, &world, None);
+    assert!(lines[0].ends_with("uses Cleave"));
+    assert_eq!(heroes[0].borrow().stat().curr_mp, 2);
+    assert_eq!(heroes[0].borrow().skills()[0].ready_in, 2);
+    assert!(enemies.iter().all(|e| e.borrow().stat().curr_hp == 8));
+
+    // on cooldown and out of mana, back to plain attacks
+    let lines = take_turn(hero_id, &world, None);
+    assert!(lines[0].contains("hits"));
+}
+
+#[test]
+fn test_skill_without_targets_costs_nothing() {
+    let mut hero = Hero::new();
+    hero.stat.curr_mp = 5;
+    hero.skills = vec![SkillSlot::new(Box::new(Strike {
+        name: "Smite".to_owned(),
+        kind: DamageKind::Magical,
+        element: None,
+        power: 1.,
+        target: Target::Enemy,
+        cost: 5,
+        cooldown: 2,
+        effect: None,
+    }))];
+    let world = test_world(vec![hero], vec![Enemy::new()]);
+    let (allies, opponents) = (world.heroes_dyn(), world.enemies_dyn());
+    let missing = opponents[0].borrow().id() + 100;
+
+    let lines = use_skill(&allies[0], 0, missing, &allies, &opponents, &mut test_rng());
+    assert!(lines.is_empty());
+    assert_eq!(allies[0].borrow().stat().curr_mp, 5);
+    assert_eq!(allies[0].borrow().skills()[0].ready_in, 0);
+}
+
+#[test]
+fn test_ordered_turn_overrides_policy() {
+    let hero = Hero::new();
+    let hero_id = hero.id;
+    let mut weak = Enemy::new();
+    weak.stat_and_status_mut().0.curr_hp = 3;
+    let strong = Enemy::new();
+    let action = attack(&hero, &strong);
+    let world = test_world(vec![hero], vec![weak, strong]);
+    let (heroes, enemies) = (world.heroes(), world.enemies());
+
+    take_ordered_turn(hero_id, &world, action);
+    assert_eq!(enemies[0].borrow().stat().curr_hp, 3);
+    assert_eq!(enemies[1].borrow().stat().curr_hp, 8);
+
+    // a stunned hero loses the order
+    heroes[0].borrow_mut().apply_effect(Box::new(Stun { duration: 1 }));
+    let lines = take_ordered_turn(hero_id, &world, action);
+    assert!(lines.iter().any(|l| l.ends_with("can't move")));
+    assert_eq!(enemies[1].borrow().stat().curr_hp, 8);
+}
+
+#[test]
+fn test_crits_misses_and_weaknesses() {
+    let mut rng = test_rng();
+    let mut hero = Hero::new();
+
+    hero.stat.crit_chance = 100;
+    let mut enemy = Enemy::new();
+    let outcome = resolve(&hero, attack(&hero, &enemy), &mut enemy, &mut rng).unwrap();
+    assert_eq!(outcome.notes, vec!["CRIT!"]);
+    // 7 and half again
+    assert_eq!(outcome.damage, 10);
+    assert!(outcome.to_string().ends_with("for 10 physical damage (CRIT!)"));
+
+    hero.stat.crit_chance = 0;
+    let mut dodgy = Enemy::new();
+    dodgy.stat_mut().evasion = 100;
+    let outcome = resolve(&hero, attack(&hero, &dodgy), &mut dodgy, &mut rng).unwrap();
+    assert!(outcome.missed);
+    assert_eq!(dodgy.stat().curr_hp, dodgy.stat().max_hp);
+    assert!(outcome.to_string().ends_with(": MISS"));
+
+    let mut weak = BasicStat::default();
+    weak.resistances.fire = -50;
+    let hit = Hit::default();
+    assert_eq!(hit_damage(&hero.stat, &weak, DamageKind::Magical, Some(Element::Fire), hit), 10);
+    assert_eq!(hit_damage(&hero.stat, &weak, DamageKind::Magical, None, hit), 7);
+    assert_eq!(notes(&weak, Some(Element::Fire), hit), vec!["weak to fire"]);
+}
+
+#[test]
+fn test_elemental_attacks_in_the_log() {
+    let mut hero = Hero::new();
+    let hero_id = hero.id;
+    hero.stat.crit_chance = 100;
+    hero.equipment.weapon = Some(Gear {
+        item: "torch".to_owned(),
+        name: "Torch".to_owned(),
+        slot: Slot::Weapon,
+        modifiers: Vec::new(),
+        element: Some(Element::Fire),
+    });
+    let mut enemy = Enemy::new();
+    enemy.stat_mut().resistances.fire = -50;
+    enemy.stat_mut().max_hp = 100;
+    enemy.stat_mut().curr_hp = 100;
+    let world = test_world(vec![hero], vec![enemy]);
+
+    let lines = take_turn(hero_id, &world, None);
+    assert!(lines.iter().any(|l| l.contains("fire damage (CRIT!, weak to fire)")), "{:?}", lines);
+
+    world.enemies()[0].borrow_mut().stat_mut().evasion = 100;
+    world.heroes()[0].borrow_mut().stat.accuracy = 0;
+    let lines = take_turn(hero_id, &world, None);
+    assert!(lines.iter().any(|l| l.ends_with(": MISS")), "{:?}", lines);
+}
+
+#[test]
+fn test_immunity_keeps_effects_off() {
+    let mut enemy = Enemy::new();
+    enemy.stat_mut().resistances.fire = 100;
+    let lines = enemy.apply_effect(Box::new(Burn { damage: 3, duration: 2 }));
+    assert_eq!(lines, vec!["is immune to burn".to_string()]);
+    assert!(enemy.status().effects().is_empty());
+    enemy.apply_effect(Box::new(DragonPoison { damage: 3, duration: 2 }));
+    assert_eq!(enemy.status().effects().len(), 1);
+}
@@ -0,0 +1,563 @@
+use std::fmt::Display;
+
+use anyhow::Result;
+
+use crate::Context;
+
+use super::states::StateType;
+
+pub mod parser;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArgKind {
+    Choice(&'static [&'static str]),
+    Multiplier { max: u32 },
+    Command,
+    Slot,
+    // a 1-based list index or a name
+    Entity,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub required: bool,
+}
+
+impl ArgSpec {
+    pub const fn required(name: &'static str, kind: ArgKind) -> Self {
+        Self {
+            name,
+            kind,
+            required: true,
+        }
+    }
+
+    pub const fn optional(name: &'static str, kind: ArgKind) -> Self {
+        Self {
+            name,
+            kind,
+            required: false,
+        }
+    }
+
+    fn parse(&self, token: &str) -> Result<Value, CommandError> {
+        let bad = |expected: String| CommandError::BadArg {
+            arg: self.name,
+            value: token.to_owned(),
+            expected,
+        };
+        match self.kind {
+            ArgKind::Command | ArgKind::Slot => Ok(Value::Text(token.to_owned())),
+            ArgKind::Entity => Ok(token
+                .parse::<i64>()
+                .map(Value::Int)
+                .unwrap_or_else(|_| Value::Text(token.to_owned()))),
+            ArgKind::Choice(choices) => choices
+                .iter()
+                .find(|c| c.eq_ignore_ascii_case(token))
+                .map(|c| Value::Text((*c).to_owned()))
+                .ok_or_else(|| bad(format!("one of {}", choices.join(", ")))),
+            ArgKind::Multiplier { max } => token
+                .strip_suffix(['x', 'X'])
+                .unwrap_or(token)
+                .parse::<u32>()
+                .ok()
+                .filter(|n| (1..=max).contains(n))
+                .map(Value::Multiplier)
+                .ok_or_else(|| bad(format!("a multiplier from 1x to {}x", max))),
+        }
+    }
+}
+
+impl Display for ArgSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self.kind {
+            ArgKind::Choice(choices) => choices.join("|"),
+            _ => self.name.to_owned(),
+        };
+        if self.required {
+            write!(f, "<{}>", name)
+        } else {
+            write!(f, "[{}]", name)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Text(String),
+    Multiplier(u32),
+}
+
+#[derive(Debug, Default)]
+pub struct Args {
+    values: Vec<(&'static str, Value)>,
+}
+
+impl Args {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.iter().find(|(n, _)| *n == name).map(|(_, v)| v)
+    }
+
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match self.get(name) {
+            Some(Value::Text(s)) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn multiplier(&self, name: &str) -> Option<u32> {
+        match self.get(name) {
+            Some(Value::Multiplier(n)) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+pub type Handler<S> = fn(&mut S, &Args, &mut Context) -> Result<Option<StateType>>;
+
+pub struct CommandSpec<S> {
+    pub name: &'static str,
+    pub aliases: Vec<&'static str>,
+    pub args: Vec<ArgSpec>,
+    pub help: &'static str,
+    pub handler: Handler<S>,
+}
+
+impl<S> CommandSpec<S> {
+    pub fn new(name: &'static str, help: &'static str, handler: Handler<S>) -> Self {
+        Self {
+            name,
+            aliases: Vec::new(),
+            args: Vec::new(),
+            help,
+            handler,
+        }
+    }
+
+    pub fn alias(mut self, alias: &'static str) -> Self {
+        self.aliases.push(alias);
+        self
+    }
+
+    pub fn arg(mut self, arg: ArgSpec) -> Self {
+        self.args.push(arg);
+        self
+    }
+
+    pub fn usage(&self) -> String {
+        std::iter::once(self.name.to_owned())
+            .chain(self.args.iter().map(|a| a.to_string()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Completion {
+    // byte offset of the token being completed
+    pub start: usize,
+    pub candidates: Vec<String>,
+}
+
+pub type Source<'a> = &'a dyn Fn(ArgKind, &[String]) -> Vec<String>;
+
+pub struct Invocation<S> {
+    pub handler: Handler<S>,
+    pub args: Args,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CommandError {
+    Empty,
+    UnterminatedQuote,
+    DanglingEscape,
+    Unknown {
+        name: String,
+        suggestion: Option<&'static str>,
+    },
+    MissingArg {
+        usage: String,
+        arg: &'static str,
+    },
+    BadArg {
+        arg: &'static str,
+        value: String,
+        expected: String,
+    },
+    TooManyArgs {
+        usage: String,
+    },
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::Empty => write!(f, "empty command, try 'help'"),
+            CommandError::UnterminatedQuote => write!(f, "missing closing quote"),
+            CommandError::DanglingEscape => write!(f, "nothing to escape after '\\'"),
+            CommandError::Unknown {
+                name,
+                suggestion: Some(suggestion),
+            } => write!(f, "unknown command '{}', did you mean '{}'?", name, suggestion),
+            CommandError::Unknown { name, .. } => {
+                write!(f, "unknown command '{}', try 'help'", name)
+            }
+            CommandError::MissingArg { usage, arg } => {
+                write!(f, "missing {}, usage: {}", arg, usage)
+            }
+            CommandError::BadArg {
+                arg,
+                value,
+                expected,
+            } => write!(f, "bad {} '{}', expected {}", arg, value, expected),
+            CommandError::TooManyArgs { usage } => {
+                write!(f, "too many arguments, usage: {}", usage)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+pub struct Registry<S> {
+    commands: Vec<CommandSpec<S>>,
+}
+
+impl<S> Registry<S> {
+    pub fn new() -> Self {
+        Self {
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn register(mut self, command: CommandSpec<S>) -> Self {
+        self.commands.push(command);
+        self
+    }
+
+    pub fn find(&self, name: &str) -> Option<&CommandSpec<S>> {
+        self.commands.iter().find(|c| c.matches(name))
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Invocation<S>, CommandError> {
+        let tokens = parser::tokenize(input)?;
+        let Some((name, tokens)) = tokens.split_first() else {
+            return Err(CommandError::Empty);
+        };
+        let name = name.to_lowercase();
+        let command = self.find(&name).ok_or_else(|| CommandError::Unknown {
+            suggestion: self.suggest(&name),
+            name,
+        })?;
+        if tokens.len() > command.args.len() {
+            return Err(CommandError::TooManyArgs {
+                usage: command.usage(),
+            });
+        }
+
+        let mut args = Args::default();
+        for (i, spec) in command.args.iter().enumerate() {
+            match tokens.get(i) {
+                Some(token) => args.values.push((spec.name, spec.parse(token)?)),
+                None if spec.required => {
+                    return Err(CommandError::MissingArg {
+                        usage: command.usage(),
+                        arg: spec.name,
+                    })
+                }
+                None => (),
+            }
+        }
+        Ok(Invocation {
+            handler: command.handler,
+            args,
+        })
+    }
+
+    pub fn help(&self, name: Option<&str>) -> Result<Vec<String>, CommandError> {
+        match name {
+            None => Ok(self
+                .commands
+                .iter()
+                .map(|c| format!("{} - {}", c.usage(), c.help))
+                .collect()),
+            Some(name) => {
+                let command = self.find(name).ok_or_else(|| CommandError::Unknown {
+                    name: name.to_owned(),
+                    suggestion: self.suggest(name),
+                })?;
+                let mut lines = vec![format!("{} - {}", command.usage(), command.help)];
+                if !command.aliases.is_empty() {
+                    lines.push(format!("aliases: {}", command.aliases.join(", ")));
+                }
+                Ok(lines)
+            }
+        }
+    }
+
+    pub fn complete(&self, input: &str, source: Source) -> Completion {
+        let start = parser::last_token_start(input);
+        let partial = input[start..].trim_start_matches('"').to_lowercase();
+        let tokens = parser::tokenize(&input[..start]).unwrap_or_default();
+
+        let options: Vec<String> = match tokens.split_first() {
+            None => self.commands.iter().map(|c| c.name.to_owned()).collect(),
+            Some((name, args)) => match self
+                .find(&name.to_lowercase())
+                .and_then(|c| c.args.get(args.len()))
+            {
+                Some(spec) => match spec.kind {
+                    ArgKind::Choice(choices) => choices.iter().map(|c| (*c).to_owned()).collect(),
+                    ArgKind::Command => self.commands.iter().map(|c| c.name.to_owned()).collect(),
+                    kind => source(kind, args),
+                },
+                None => Vec::new(),
+            },
+        };
+        Completion {
+            start,
+            candidates: options
+                .into_iter()
+                .filter(|o| o.to_lowercase().starts_with(&partial))
+                .map(|o| parser::quote(&o))
+                .collect(),
+        }
+    }
+
+    // the unfinished part of the best match followed by the arguments still to be typed
+    pub fn hint(&self, input: &str, source: Source) -> Option<String> {
+        let completion = self.complete(input, source);
+        let typed = input[completion.start..].chars().count();
+        let rest: String = match completion.candidates.first() {
+            Some(best) if typed > 0 => best.chars().skip(typed).collect(),
+            _ => String::new(),
+        };
+        let full = format!("{}{}", input, rest);
+        let tokens = parser::tokenize(&full).ok()?;
+        let command = self.find(&tokens.first()?.to_lowercase())?;
+        let remaining: Vec<String> = command
+            .args
+            .iter()
+            .skip(tokens.len() - 1)
+            .map(|a| a.to_string())
+            .collect();
+
+        let mut hint = rest;
+        if !remaining.is_empty() {
+            if !full.ends_with(char::is_whitespace) {
+                hint.push(' ');
+            }
+            hint.push_str(&remaining.join(" "));
+        }
+        Some(hint).filter(|h| !h.is_empty())
+    }
+
+    fn suggest(&self, name: &str) -> Option<&'static str> {
+        self.commands
+            .iter()
+            .flat_map(|c| std::iter::once(c.name).chain(c.aliases.iter().copied()))
+            .map(|n| (parser::distance(name, n), n))
+            .filter(|(d, _)| *d <= 2)
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, n)| n)
+    }
+}
+
+#[cfg(test)]
+struct Counter {
+    n: i64,
+}
+
+#[cfg(test)]
+fn counter_commands() -> Registry<Counter> {
+    Registry::new()
+        .register(
+            CommandSpec::new("add", "add to the counter", |s: &mut Counter, args, _| {
+                s.n += args.multiplier("amount").unwrap_or(1) as i64;
+                Ok(None)
+            })
+            .alias("+")
+            .arg(ArgSpec::optional("amount", ArgKind::Multiplier { max: 10 })),
+        )
+        .register(
+            CommandSpec::new("speed", "set speed", |s: &mut Counter, args, _| {
+                s.n = args.multiplier("speed").unwrap() as i64;
+                Ok(None)
+            })
+            .arg(ArgSpec::required("speed", ArgKind::Multiplier { max: 4 })),
+        )
+        .register(
+            CommandSpec::new("menu", "go back", |_: &mut Counter, _, _| {
+                Ok(Some(StateType::Welcome))
+            })
+            .arg(ArgSpec::required("side", ArgKind::Choice(&["hero", "enemy"]))),
+        )
+}
+
+#[cfg(test)]
+fn run(registry: &Registry<Counter>, counter: &mut Counter, input: &str) -> Result<Option<StateType>> {
+    let invocation = registry.parse(input)?;
+    (invocation.handler)(counter, &invocation.args, &mut Context::default())
+}
+
+#[test]
+fn test_dispatch() {
+    let registry = counter_commands();
+    let mut counter = Counter { n: 0 };
+    run(&registry, &mut counter, "add").unwrap();
+    run(&registry, &mut counter, ":add 5").unwrap();
+    run(&registry, &mut counter, "+ 2x").unwrap();
+    assert_eq!(counter.n, 8);
+    run(&registry, &mut counter, "SPEED 2x").unwrap();
+    assert_eq!(counter.n, 2);
+    assert_eq!(
+        run(&registry, &mut counter, "menu Enemy").unwrap(),
+        Some(StateType::Welcome)
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let registry = counter_commands();
+    let err = |input: &str| registry.parse(input).err().unwrap();
+    assert_eq!(err("  "), CommandError::Empty);
+    assert_eq!(
+        err("ad 1"),
+        CommandError::Unknown {
+            name: "ad".to_owned(),
+            suggestion: Some("add")
+        }
+    );
+    assert_eq!(
+        err("xyzzy"),
+        CommandError::Unknown {
+            name: "xyzzy".to_owned(),
+            suggestion: None
+        }
+    );
+    assert_eq!(
+        err("add 11"),
+        CommandError::BadArg {
+            arg: "amount",
+            value: "11".to_owned(),
+            expected: "a multiplier from 1x to 10x".to_owned()
+        }
+    );
+    assert_eq!(
+        err("add 1 2"),
+        CommandError::TooManyArgs {
+            usage: "add [amount]".to_owned()
+        }
+    );
+    assert_eq!(
+        err("speed"),
+        CommandError::MissingArg {
+            usage: "speed <speed>".to_owned(),
+            arg: "speed"
+        }
+    );
+    assert!(matches!(err("speed 5x"), CommandError::BadArg { .. }));
+    assert!(matches!(err("menu boss"), CommandError::BadArg { .. }));
+}
+
+#[test]
+fn test_help() {
+    let registry = counter_commands();
+    assert_eq!(
+        registry.help(None).unwrap(),
+        vec![
+            "add [amount] - add to the counter",
+            "speed <speed> - set speed",
+            "menu <hero|enemy> - go back"
+        ]
+    );
+    assert_eq!(
+        registry.help(Some("+")).unwrap(),
+        vec!["add [amount] - add to the counter", "aliases: +"]
+    );
+    assert!(registry.help(Some("nope")).is_err());
+}
+
+#[cfg(test)]
+fn names(kind: ArgKind, args: &[String]) -> Vec<String> {
+    match (kind, args.first().map(|s| s.as_str())) {
+        (ArgKind::Slot, _) => vec!["auto".to_owned(), "slot1".to_owned()],
+        (ArgKind::Entity, Some("hero")) => vec!["1. Hero".to_owned()],
+        (ArgKind::Entity, _) => vec!["7. Enemy".to_owned(), "9. Enemy".to_owned()],
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+fn completion_commands() -> Registry<Counter> {
+    counter_commands()
+        .register(
+            CommandSpec::new("load", "load", |_: &mut Counter, _, _| Ok(None))
+                .arg(ArgSpec::optional("slot", ArgKind::Slot)),
+        )
+        .register(
+            CommandSpec::new("target", "target", |_: &mut Counter, _, _| Ok(None))
+                .arg(ArgSpec::required("side", ArgKind::Choice(&["hero", "enemy"])))
+                .arg(ArgSpec::required("entity", ArgKind::Entity)),
+        )
+        .register(
+            CommandSpec::new("help", "help", |_: &mut Counter, _, _| Ok(None))
+                .arg(ArgSpec::optional("command", ArgKind::Command)),
+        )
+}
+
+#[test]
+fn test_complete() {
+    let registry = completion_commands();
+    let complete = |input: &str| registry.complete(input, &names);
+    assert_eq!(
+        complete(""),
+        Completion {
+            start: 0,
+            candidates: vec!["add", "speed", "menu", "load", "target", "help"]
+                .into_iter()
+                .map(String::from)
+                .collect()
+        }
+    );
+    assert_eq!(complete("s").candidates, vec!["speed"]);
+    assert_eq!(
+        complete("load ").candidates,
+        vec!["auto".to_owned(), "slot1".to_owned()]
+    );
+    assert_eq!(complete("target E").candidates, vec!["enemy"]);
+    assert_eq!(
+        complete("target enemy ").candidates,
+        vec![r#""7. Enemy""#, r#""9. Enemy""#]
+    );
+    assert_eq!(
+        complete(r#"target hero "1."#),
+        Completion {
+            start: 12,
+            candidates: vec![r#""1. Hero""#.to_owned()]
+        }
+    );
+    assert_eq!(complete("help m").candidates, vec!["menu"]);
+    assert!(complete("nope ").candidates.is_empty());
+    assert!(complete("add 1 ").candidates.is_empty());
+}
+
+#[test]
+fn test_hint() {
+    let registry = completion_commands();
+    let hint = |input: &str| registry.hint(input, &names);
+    assert_eq!(hint("tar").as_deref(), Some("get <hero|enemy> <entity>"));
+    assert_eq!(hint("target ").as_deref(), Some("<hero|enemy> <entity>"));
+    assert_eq!(hint("target e").as_deref(), Some("nemy <entity>"));
+    assert_eq!(hint("target enemy 2"), None);
+    assert_eq!(hint("lo").as_deref(), Some("ad [slot]"));
+    assert_eq!(hint("xyz"), None);
+}
@@ -0,0 +1,115 @@
+use super::CommandError;
+
+// whitespace separated words, "double quotes" keep spaces and \ escapes the next char
+pub fn tokenize(input: &str) -> Result<Vec<String>, CommandError> {
+    let input = input.trim();
+    let input = input.strip_prefix(':').unwrap_or(input);
+    let mut tokens = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quoted = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let escaped = chars.next().ok_or(CommandError::DanglingEscape)?;
+                current.get_or_insert_with(String::new).push(escaped);
+            }
+            '"' => {
+                quoted = !quoted;
+                current.get_or_insert_with(String::new);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if let Some(token) = current.take() {
+                    tokens.push(token);
+                }
+            }
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quoted {
+        return Err(CommandError::UnterminatedQuote);
+    }
+    tokens.extend(current);
+    Ok(tokens)
+}
+
+// where the token under construction at the end of the input begins
+pub fn last_token_start(input: &str) -> usize {
+    let mut start = 0;
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in input.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if c.is_whitespace() && !quoted {
+            start = i + c.len_utf8();
+        }
+    }
+    start
+}
+
+pub fn quote(token: &str) -> String {
+    if token.contains(|c: char| c.is_whitespace() || c == '"' || c == '\\') {
+        format!("\"{}\"", token.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        token.to_owned()
+    }
+}
+
+pub fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            row[j + 1] = (prev[j] + (ca != *cb) as usize)
+                .min(prev[j + 1] + 1)
+                .min(row[j] + 1);
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+#[test]
+fn test_tokenize() {
+    assert_eq!(tokenize("  save  slot1 ").unwrap(), vec!["save", "slot1"]);
+    assert_eq!(tokenize(":target enemy 2").unwrap(), vec!["target", "enemy", "2"]);
+    assert_eq!(
+        tokenize(r#"say "hello world" a\ b"#).unwrap(),
+        vec!["say", "hello world", "a b"]
+    );
+    assert_eq!(tokenize(r#"x """#).unwrap(), vec!["x", ""]);
+    assert!(tokenize("").unwrap().is_empty());
+    assert_eq!(tokenize(r#"say "oops"#), Err(CommandError::UnterminatedQuote));
+    assert_eq!(tokenize(r"say oops\"), Err(CommandError::DanglingEscape));
+}
+
+#[test]
+fn test_distance() {
+    assert_eq!(distance("save", "save"), 0);
+    assert_eq!(distance("sve", "save"), 1);
+    assert_eq!(distance("lod", "load"), 1);
+    assert_eq!(distance("quit", "help"), 4);
+}
+
+#[test]
+fn test_last_token_start() {
+    assert_eq!(last_token_start(""), 0);
+    assert_eq!(last_token_start("save"), 0);
+    assert_eq!(last_token_start("save "), 5);
+    assert_eq!(last_token_start("save sl"), 5);
+    assert_eq!(last_token_start(r#"target "9. En"#), 7);
+}
+
+#[test]
+fn test_quote_roundtrips() {
+    for token in ["plain", "9. Enemy", r#"say "hi""#, r"back\slash"] {
+        assert_eq!(tokenize(&quote(token)).unwrap(), vec![token]);
+    }
+}
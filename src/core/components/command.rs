@@ -1,20 +1,64 @@
+use std::collections::VecDeque;
+
+use crossterm::event::{KeyCode, KeyModifiers};
 use ratatui::{
-    layout::{Alignment, Rect},
+    layout::Rect,
     style::{Style, Stylize},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use crate::{
+    core::{
+        commands::Completion,
+        consts::{ACCENT, PRIMARY},
+        history::History,
+    },
+    Event,
+};
 
-use crate::{core::consts::{ACCENT, PRIMARY}, Event};
+use super::Component;
 
-use super::{Component, ComponentType};
+// how many kills ctrl-y and alt-y can bring back
+const KILL_RING: usize = 16;
 
 #[derive(Debug)]
 pub struct Command {
     content: String,
     enter: bool,
+    // byte index of the cursor in content, always on a grapheme boundary
     cursor_offset: usize,
+    // first visible byte when the line is wider than the widget
+    scroll: usize,
+    // newest first, like readline's kill ring
+    kills: VecDeque<String>,
+    chain: Option<Chain>,
+    completion: Option<(Completion, usize)>,
+    hint: Option<String>,
+    history: History,
+    // position while browsing with up/down, and what was typed before that
+    history_idx: Option<usize>,
+    draft: String,
+    search: Option<Search>,
+}
+
+// the line right after a kill or yank. if nothing touched it since, the next kill joins the
+// last one and alt-y can swap the yanked text for an older kill
+#[derive(Debug)]
+struct Chain {
+    content: String,
+    cursor: usize,
+    // where the yanked text starts and which kill it is
+    yank: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Default)]
+struct Search {
+    query: String,
+    found: Option<usize>,
 }
 
 impl Command {
@@ -23,72 +67,387 @@ impl Command {
             content: String::new(),
             enter: false,
             cursor_offset: 0,
+            scroll: 0,
+            kills: VecDeque::new(),
+            chain: None,
+            completion: None,
+            hint: None,
+            history: History::default(),
+            history_idx: None,
+            draft: String::new(),
+            search: None,
         }
     }
 
-    pub fn push(&mut self, c: char) {
-        if self.enter {
-            self.content.push(c);
+    pub fn set_history(&mut self, history: History) {
+        self.history = history;
+        self.history_idx = None;
+    }
+
+    fn set_content(&mut self, content: String) {
+        self.cursor_offset = content.len();
+        self.content = content;
+        self.scroll = 0;
+        self.completion = None;
+    }
+
+    fn prev_boundary(&self) -> usize {
+        self.content[..self.cursor_offset]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.content[self.cursor_offset..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor_offset, |g| self.cursor_offset + g.len())
+    }
+
+    // words are runs of non whitespace, like in a shell
+    fn word_start(&self) -> usize {
+        let mut start = self.cursor_offset;
+        let mut in_word = false;
+        for (i, g) in self.content[..self.cursor_offset].grapheme_indices(true).rev() {
+            let space = g.chars().all(char::is_whitespace);
+            if space && in_word {
+                break;
+            }
+            in_word |= !space;
+            start = i;
         }
+        start
     }
 
-    pub fn pop(&mut self) {
-        if self.enter {
-            self.content.pop();
+    fn word_end(&self) -> usize {
+        let mut end = self.cursor_offset;
+        let mut in_word = false;
+        for g in self.content[self.cursor_offset..].graphemes(true) {
+            let space = g.chars().all(char::is_whitespace);
+            if space && in_word {
+                break;
+            }
+            in_word |= !space;
+            end += g.len();
         }
+        end
     }
 
-    pub fn pop_word(&mut self) {
-        if self.enter {
-            let mut deleted_a_char = false;
-            while let Some(c) = self.content.pop() {
-                if matches!(c, 'a'..='z' | 'A'..='Z') {
-                    deleted_a_char = true;
-                } else if c == ' ' && deleted_a_char {
-                    self.content.push(' ');
-                    break;
+    fn move_to(&mut self, offset: usize) {
+        self.cursor_offset = offset;
+        self.completion = None;
+    }
+
+    fn replace(&mut self, start: usize, end: usize, text: &str) {
+        self.content.replace_range(start..end, text);
+        self.cursor_offset = start + text.len();
+    }
+
+    fn delete(&mut self, start: usize, end: usize) {
+        self.replace(start, end, "");
+        self.completion = None;
+    }
+
+    fn chained(&self) -> Option<&Chain> {
+        self.chain
+            .as_ref()
+            .filter(|c| c.content == self.content && c.cursor == self.cursor_offset)
+    }
+
+    fn set_chain(&mut self, yank: Option<(usize, usize)>) {
+        self.chain = Some(Chain {
+            content: self.content.clone(),
+            cursor: self.cursor_offset,
+            yank,
+        });
+    }
+
+    // killing backwards puts the text in front of the last kill, forwards after it
+    fn kill(&mut self, start: usize, end: usize) {
+        if start >= end {
+            return;
+        }
+        let text = &self.content[start..end];
+        let joins = self.chained().is_some_and(|c| c.yank.is_none());
+        match self.kills.front_mut() {
+            Some(last) if joins && end == self.cursor_offset => last.insert_str(0, text),
+            Some(last) if joins => last.push_str(text),
+            _ => {
+                self.kills.push_front(text.to_owned());
+                self.kills.truncate(KILL_RING);
+            }
+        }
+        self.delete(start, end);
+        self.set_chain(None);
+    }
+
+    fn yank(&mut self) {
+        let Some(killed) = self.kills.front().cloned() else {
+            return;
+        };
+        let start = self.cursor_offset;
+        self.replace(start, start, &killed);
+        self.completion = None;
+        self.set_chain(Some((start, 0)));
+    }
+
+    // only right after a yank, swaps it for the kill before it and wraps around at the oldest
+    fn yank_pop(&mut self) {
+        let Some((start, idx)) = self.chained().and_then(|c| c.yank) else {
+            return;
+        };
+        let idx = (idx + 1) % self.kills.len();
+        let killed = self.kills[idx].clone();
+        self.replace(start, self.cursor_offset, &killed);
+        self.set_chain(Some((start, idx)));
+    }
+
+    fn history_prev(&mut self) {
+        let idx = match self.history_idx {
+            Some(idx) => idx.saturating_sub(1),
+            None if self.history.len() > 0 => {
+                self.draft = self.content.clone();
+                self.history.len() - 1
+            }
+            None => return,
+        };
+        self.history_idx = Some(idx);
+        self.set_content(self.history.get(idx).unwrap_or_default().to_owned());
+    }
+
+    fn history_next(&mut self) {
+        let Some(idx) = self.history_idx else {
+            return;
+        };
+        if idx + 1 < self.history.len() {
+            self.history_idx = Some(idx + 1);
+            self.set_content(self.history.get(idx + 1).unwrap_or_default().to_owned());
+        } else {
+            self.history_idx = None;
+            let draft = std::mem::take(&mut self.draft);
+            self.set_content(draft);
+        }
+    }
+
+    // ctrl-r starts a reverse search, pressing it again jumps to the next older match
+    fn search_older(&mut self) {
+        let len = self.history.len();
+        match self.search.as_mut() {
+            Some(search) => {
+                let before = search.found.unwrap_or(len);
+                if let Some(found) = self.history.search(&search.query, before) {
+                    search.found = Some(found);
                 }
             }
+            None => self.search = Some(Search::default()),
+        }
+    }
+
+    fn search_from(&mut self, before: usize) {
+        if let Some(search) = self.search.as_mut() {
+            search.found = self.history.search(&search.query, before);
         }
     }
 
+    pub fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.search = None;
+    }
+
+    fn accept_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            if let Some(entry) = search.found.and_then(|idx| self.history.get(idx)) {
+                self.set_content(entry.to_owned());
+            }
+        }
+    }
+
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    pub fn before_cursor(&self) -> &str {
+        &self.content[..self.cursor_offset]
+    }
+
+    pub fn set_hint(&mut self, hint: Option<String>) {
+        self.hint = hint;
+    }
+
+    // a lone candidate is accepted right away, several are cycled through by repeated calls
+    pub fn complete(&mut self, completion: Completion) {
+        match completion.candidates.len() {
+            0 => (),
+            1 => {
+                let candidate = format!("{} ", completion.candidates[0]);
+                self.replace(completion.start, self.cursor_offset, &candidate);
+                self.completion = None;
+            }
+            _ => {
+                self.replace(completion.start, self.cursor_offset, &completion.candidates[0]);
+                self.completion = Some((completion, 0));
+            }
+        }
+    }
+
+    pub fn cycle_completion(&mut self, forward: bool) -> bool {
+        let Some((completion, idx)) = self.completion.take() else {
+            return false;
+        };
+        let len = completion.candidates.len();
+        let next = if forward { (idx + 1) % len } else { (idx + len - 1) % len };
+        self.replace(completion.start, self.cursor_offset, &completion.candidates[next]);
+        self.completion = Some((completion, next));
+        true
+    }
+
+    pub fn push(&mut self, c: char) {
+        if let Some(search) = self.search.as_mut() {
+            search.query.push(c);
+            // a longer query can still match the current entry
+            let before = search.found.map_or(self.history.len(), |idx| idx + 1);
+            self.search_from(before);
+        } else if self.enter {
+            self.content.insert(self.cursor_offset, c);
+            self.cursor_offset += c.len_utf8();
+            self.completion = None;
+        }
+    }
+
+    pub fn pop(&mut self) {
+        if let Some(search) = self.search.as_mut() {
+            search.query.pop();
+            self.search_from(self.history.len());
+        } else if self.enter {
+            self.delete(self.prev_boundary(), self.cursor_offset);
+        }
+    }
+
+    // while searching, enter only accepts the match into the line
     pub fn execute(&mut self) -> Option<String> {
-        let result = self.content.clone();
-        self.content.clear();
+        if self.search.is_some() {
+            self.accept_search();
+            return None;
+        }
+        let result = std::mem::take(&mut self.content);
+        self.cursor_offset = 0;
+        self.scroll = 0;
+        // history is a convenience, failing to write it shouldn't block the command
+        let _ = self.history.push(&result);
+        self.history_idx = None;
+        self.draft.clear();
+        self.completion = None;
+        self.hint = None;
         Some(result)
     }
+
+    // the text to show and where the cursor sits in it
+    fn rendered_text(&self) -> (String, usize) {
+        match &self.search {
+            Some(search) => (
+                format!(
+                    "`{}': {}",
+                    search.query,
+                    search
+                        .found
+                        .and_then(|idx| self.history.get(idx))
+                        .unwrap_or_default()
+                ),
+                search.query.len() + 1,
+            ),
+            None => (self.content.clone(), self.cursor_offset),
+        }
+    }
+}
+
+// visible byte range of `text` in `width` columns, scrolled just enough to keep the cursor in view
+fn viewport(text: &str, cursor: usize, scroll: usize, width: usize) -> (usize, usize) {
+    let mut start = scroll.min(cursor);
+    // the cursor needs a column of its own
+    while start < cursor && text[start..cursor].width() >= width {
+        start += text[start..].graphemes(true).next().map_or(0, str::len);
+    }
+    let mut end = start;
+    let mut used = 0;
+    for g in text[start..].graphemes(true) {
+        used += g.width();
+        if used > width {
+            break;
+        }
+        end += g.len();
+    }
+    (start, end)
 }
 
 impl Component for Command {
-    fn handle_event(&mut self, _event: &Event) {
-        
+    fn handle_event(&mut self, event: &Event) {
+        if !self.enter {
+            return;
+        }
+        if let Event::Key(k) = event {
+            let ctrl = k.modifiers == KeyModifiers::CONTROL;
+            let alt = k.modifiers == KeyModifiers::ALT;
+            if ctrl && k.code == KeyCode::Char('r') {
+                self.search_older();
+                return;
+            }
+            // any other editing key leaves the search with the current match
+            if self.search.is_some()
+                && !matches!(k.code, KeyCode::Char(_) | KeyCode::Backspace | KeyCode::Enter | KeyCode::Esc)
+            {
+                self.accept_search();
+            }
+            match k.code {
+                KeyCode::Up => self.history_prev(),
+                KeyCode::Down => self.history_next(),
+                KeyCode::Left => self.move_to(self.prev_boundary()),
+                KeyCode::Right => self.move_to(self.next_boundary()),
+                KeyCode::Home => self.move_to(0),
+                KeyCode::End => self.move_to(self.content.len()),
+                KeyCode::Delete => self.delete(self.cursor_offset, self.next_boundary()),
+                KeyCode::Char(c) if ctrl => match c {
+                    'a' => self.move_to(0),
+                    'e' => self.move_to(self.content.len()),
+                    'w' => self.kill(self.word_start(), self.cursor_offset),
+                    'u' => self.kill(0, self.cursor_offset),
+                    'k' => self.kill(self.cursor_offset, self.content.len()),
+                    'y' => self.yank(),
+                    _ => (),
+                },
+                KeyCode::Char('b') if alt => self.move_to(self.word_start()),
+                KeyCode::Char('f') if alt => self.move_to(self.word_end()),
+                KeyCode::Char('y') if alt => self.yank_pop(),
+                _ => (),
+            }
+        }
     }
 
     fn render(&mut self, title: &str, frame: &mut Frame, area: Rect, selected: bool) {
         let color = if selected { ACCENT } else { PRIMARY };
-        let inner_widget_len = area.width as usize - 3;
-        let input_exceed_widget = self.content.len() >= inner_widget_len;
-
-        let rendered_content = if input_exceed_widget {
-            let offset = self
-                .content
-                .char_indices()
-                .nth_back(inner_widget_len - 1)
-                .map(|(i, _)| i)
-                .unwrap_or(0);
-            self.content[offset..].to_owned() + " "
-        } else {
-            self.content.to_owned()
-        };
+        let title = if self.search.is_some() { " (reverse-i-search) " } else { title };
+        let (text, cursor) = self.rendered_text();
+        let width = area.width.saturating_sub(2) as usize;
+        let scroll = if self.search.is_some() { 0 } else { self.scroll };
+        let (start, end) = viewport(&text, cursor, scroll, width);
+        if self.search.is_none() {
+            self.scroll = start;
+        }
+
+        let mut spans = vec![Span::raw(&text[start..end])];
+        if end == text.len() && cursor == text.len() && self.search.is_none() {
+            spans.push(Span::styled(
+                self.hint.as_deref().filter(|_| self.enter).unwrap_or(""),
+                Style::default().dim(),
+            ));
+        }
 
         frame.render_widget(
-            Paragraph::new(rendered_content)
+            Paragraph::new(Line::from(spans))
             .fg(color)
-            .alignment(if input_exceed_widget {
-                Alignment::Right
-            } else {
-                Alignment::Left
-            })
             .block(
                 Block::default()
                     .borders(Borders::ALL)
@@ -100,20 +459,12 @@ impl Component for Command {
 
         if self.enter {
             frame.set_cursor(
-                if input_exceed_widget {
-                    area.x + 1 + inner_widget_len as u16
-                } else {
-                    area.x + 1 + self.content.len() as u16
-                },
+                area.x + 1 + text[start..cursor].width() as u16,
                 area.y + 1,
             );
         }
     }
 
-    fn get_type(&self) -> ComponentType {
-        ComponentType::Command
-    }
-
     fn enter(&mut self) {
         self.enter = true;
     }
@@ -124,5 +475,170 @@ impl Component for Command {
 
     fn exit(&mut self) {
         self.enter = false;
+        self.completion = None;
+        self.search = None;
+        self.history_idx = None;
+    }
+}
+
+#[cfg(test)]
+use crossterm::event::KeyEvent;
+
+#[cfg(test)]
+fn test_command(entries: &[&str]) -> Command {
+    let mut command = Command::new();
+    let mut history = History::new(10);
+    for entry in entries {
+        history.push(entry).unwrap();
+    }
+    command.set_history(history);
+    command.enter();
+    command
+}
+
+#[cfg(test)]
+fn press(command: &mut Command, code: KeyCode, modifiers: KeyModifiers) {
+    command.handle_event(&Event::Key(KeyEvent::new(code, modifiers)));
+}
+
+#[test]
+fn test_history_navigation() {
+    let mut command = test_command(&["save", "help"]);
+    command.push('x');
+    press(&mut command, KeyCode::Up, KeyModifiers::NONE);
+    assert_eq!(command.content(), "help");
+    press(&mut command, KeyCode::Up, KeyModifiers::NONE);
+    press(&mut command, KeyCode::Up, KeyModifiers::NONE);
+    assert_eq!(command.content(), "save");
+    press(&mut command, KeyCode::Down, KeyModifiers::NONE);
+    assert_eq!(command.content(), "help");
+    press(&mut command, KeyCode::Down, KeyModifiers::NONE);
+    assert_eq!(command.content(), "x");
+}
+
+#[test]
+fn test_reverse_search() {
+    let mut command = test_command(&["save a", "load a", "save b"]);
+    press(&mut command, KeyCode::Char('r'), KeyModifiers::CONTROL);
+    "sa".chars().for_each(|c| command.push(c));
+    assert_eq!(command.rendered_text().0, "`sa': save b");
+    press(&mut command, KeyCode::Char('r'), KeyModifiers::CONTROL);
+    assert_eq!(command.rendered_text().0, "`sa': save a");
+    assert_eq!(command.execute(), None);
+    assert!(!command.is_searching());
+    assert_eq!(command.content(), "save a");
+    assert_eq!(command.execute().as_deref(), Some("save a"));
+}
+
+#[cfg(test)]
+fn ctrl(command: &mut Command, c: char) {
+    press(command, KeyCode::Char(c), KeyModifiers::CONTROL);
+}
+
+#[test]
+fn test_editing_is_grapheme_aware() {
+    let mut command = test_command(&[]);
+    "héllo e\u{301}".chars().for_each(|c| command.push(c));
+    command.pop();
+    assert_eq!(command.content(), "héllo ");
+    press(&mut command, KeyCode::Home, KeyModifiers::NONE);
+    press(&mut command, KeyCode::Right, KeyModifiers::NONE);
+    press(&mut command, KeyCode::Delete, KeyModifiers::NONE);
+    command.push('e');
+    assert_eq!(command.content(), "hello ");
+    assert_eq!(command.before_cursor(), "he");
+    press(&mut command, KeyCode::End, KeyModifiers::NONE);
+    command.push('🐉');
+    press(&mut command, KeyCode::Left, KeyModifiers::NONE);
+    command.push('x');
+    assert_eq!(command.content(), "hello x🐉");
+}
+
+#[test]
+fn test_word_motion_and_kill_ring() {
+    let mut command = test_command(&[]);
+    "target enemy  Goblin".chars().for_each(|c| command.push(c));
+    ctrl(&mut command, 'w');
+    assert_eq!(command.content(), "target enemy  ");
+    ctrl(&mut command, 'y');
+    assert_eq!(command.content(), "target enemy  Goblin");
+    press(&mut command, KeyCode::Char('b'), KeyModifiers::ALT);
+    press(&mut command, KeyCode::Char('b'), KeyModifiers::ALT);
+    assert_eq!(command.before_cursor(), "target ");
+    press(&mut command, KeyCode::Char('f'), KeyModifiers::ALT);
+    assert_eq!(command.before_cursor(), "target enemy");
+    ctrl(&mut command, 'k');
+    assert_eq!(command.content(), "target enemy");
+    ctrl(&mut command, 'a');
+    ctrl(&mut command, 'y');
+    assert_eq!(command.content(), "  Goblintarget enemy");
+    ctrl(&mut command, 'e');
+    ctrl(&mut command, 'u');
+    assert_eq!(command.content(), "");
+}
+
+#[cfg(test)]
+fn alt(command: &mut Command, c: char) {
+    press(command, KeyCode::Char(c), KeyModifiers::ALT);
+}
+
+#[test]
+fn test_consecutive_kills_join() {
+    let mut command = test_command(&[]);
+    "use heal on Cleric".chars().for_each(|c| command.push(c));
+    ctrl(&mut command, 'w');
+    ctrl(&mut command, 'w');
+    assert_eq!(command.content(), "use heal ");
+    ctrl(&mut command, 'y');
+    assert_eq!(command.content(), "use heal on Cleric");
+
+    ctrl(&mut command, 'a');
+    alt(&mut command, 'f');
+    alt(&mut command, 'f');
+    ctrl(&mut command, 'k');
+    ctrl(&mut command, 'u');
+    assert_eq!(command.content(), "");
+    // the line killed in two pieces comes back in one
+    ctrl(&mut command, 'y');
+    assert_eq!(command.content(), "use heal on Cleric");
+    alt(&mut command, 'y');
+    assert_eq!(command.content(), "on Cleric");
+}
+
+#[test]
+fn test_yank_pop_cycles_older_kills() {
+    let mut command = test_command(&[]);
+    for word in ["one", "two", "three"] {
+        word.chars().for_each(|c| command.push(c));
+        ctrl(&mut command, 'u');
+        // typing in between keeps the kills apart
+        command.push(' ');
+        command.pop();
     }
+    // nothing to pop without a yank right before
+    alt(&mut command, 'y');
+    assert_eq!(command.content(), "");
+    command.push('>');
+    ctrl(&mut command, 'y');
+    assert_eq!(command.content(), ">three");
+    alt(&mut command, 'y');
+    assert_eq!(command.content(), ">two");
+    alt(&mut command, 'y');
+    alt(&mut command, 'y');
+    assert_eq!(command.content(), ">three");
+    command.push('!');
+    alt(&mut command, 'y');
+    assert_eq!(command.content(), ">three!");
+}
+
+#[test]
+fn test_viewport_scrolls_by_columns() {
+    assert_eq!(viewport("save", 4, 0, 10), (0, 4));
+    // wide chars take two columns and are never split
+    let text = "龍龍龍龍";
+    let (start, end) = viewport(text, text.len(), 0, 5);
+    assert_eq!(&text[start..end], "龍龍");
+    assert_eq!(viewport(text, 0, start, 5), (0, 6));
+    // scrolling back only happens when the cursor leaves the view
+    assert_eq!(viewport("abcdefgh", 5, 3, 4), (3, 7));
 }
@@ -0,0 +1,194 @@
+use ratatui::{
+    layout::Rect,
+    style::Stylize,
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    core::{
+        consts::{ACCENT, PRIMARY},
+        entities::{Entity, SharedEntity},
+        types::{equipment::StatKind, Element},
+    },
+    Event,
+};
+
+use super::Component;
+
+const BAR_WIDTH: usize = 20;
+
+// everything about the entity selected in an entered list, redrawn from the live entity every frame
+#[derive(Debug)]
+pub struct Details {
+    entity: Option<SharedEntity>,
+    enter: bool,
+}
+
+impl Details {
+    pub fn new() -> Self {
+        Self {
+            entity: None,
+            enter: false,
+        }
+    }
+
+    pub fn set_entity(&mut self, entity: Option<SharedEntity>) {
+        self.entity = entity;
+    }
+}
+
+fn bar(label: &str, curr: u32, max: u32) -> String {
+    let filled = (curr as usize * BAR_WIDTH).div_ceil(max.max(1) as usize).min(BAR_WIDTH);
+    format!(
+        "{} {}{} {}/{}",
+        label,
+        "\u{2588}".repeat(filled),
+        "\u{2591}".repeat(BAR_WIDTH - filled),
+        curr,
+        max
+    )
+}
+
+pub fn lines(entity: &dyn Entity) -> Vec<String> {
+    let mut lines = Vec::new();
+    if !entity.info().description.is_empty() {
+        lines.push(entity.info().description.to_string());
+    }
+    if let Some(progress) = entity.progress() {
+        lines.push(format!(
+            "Lv {} ({}/{} xp)",
+            progress.level,
+            progress.xp,
+            progress.xp_to_next()
+        ));
+    }
+    if !entity.is_alive() {
+        lines.push("Fallen".to_owned());
+    }
+
+    let (base, stat) = (entity.stat(), entity.effective_stat());
+    lines.push(bar("HP", stat.curr_hp, stat.max_hp));
+    if stat.max_mp > 0 {
+        lines.push(bar("MP", stat.curr_mp, stat.max_mp));
+    }
+    // effective values, with what gear and effects add on top of the base,
+    // attack and defense first, then the percentages
+    for secondary in [false, true] {
+        lines.push(
+            StatKind::ALL
+                .iter()
+                .filter(|kind| kind.is_secondary() == secondary)
+                .map(|kind| {
+                    let diff = kind.get(&stat) - kind.get(base);
+                    if diff == 0 {
+                        format!("{} {}", kind.name(), kind.get(&stat))
+                    } else {
+                        format!("{} {} ({:+})", kind.name(), kind.get(&stat), diff)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("  "),
+        );
+    }
+    let affinities: Vec<String> = Element::ALL
+        .iter()
+        .filter_map(|e| stat.resistances.describe(*e))
+        .collect();
+    if !affinities.is_empty() {
+        lines.push(affinities.join(", "));
+    }
+
+    lines.push(String::new());
+    let effects = entity.status().effects();
+    if effects.is_empty() {
+        lines.push("Effects: none".to_owned());
+    } else {
+        lines.push("Effects:".to_owned());
+        lines.extend(
+            effects
+                .iter()
+                .map(|effect| format!("  {} ({} turns left)", effect.name(), effect.duration())),
+        );
+    }
+    if let Some(equipment) = entity.equipment() {
+        lines.push("Equipment:".to_owned());
+        lines.extend(
+            equipment
+                .gear()
+                .map(|gear| format!("  {}: {}", gear.slot, gear.name)),
+        );
+        if equipment.gear().next().is_none() {
+            lines.push("  nothing worn".to_owned());
+        }
+    }
+    lines.push("Skills:".to_owned());
+    lines.extend(entity.skills().iter().map(|slot| format!("  {}", slot)));
+    if entity.skills().is_empty() {
+        lines.push("  none".to_owned());
+    }
+    lines
+}
+
+impl Component for Details {
+    fn handle_event(&mut self, _event: &Event) {}
+
+    fn render(&mut self, title: &str, frame: &mut Frame, area: Rect, selected: bool) {
+        let color = if selected { ACCENT } else { PRIMARY };
+        let Some(entity) = &self.entity else {
+            return;
+        };
+        let entity = entity.borrow();
+        let title = format!("{}{} ", title, entity.info().name);
+        frame.render_widget(
+            Paragraph::new(lines(&*entity).into_iter().map(Line::from).collect::<Vec<_>>())
+                .fg(color)
+                .block(Block::default().borders(Borders::ALL).fg(color).title(title))
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+
+    fn enter(&mut self) {
+        self.enter = true;
+    }
+
+    fn is_entered(&self) -> bool {
+        self.enter
+    }
+
+    fn exit(&mut self) {
+        self.enter = false;
+    }
+}
+
+#[cfg(test)]
+use crate::core::{
+    entities::hero::Hero,
+    types::effects::Burn,
+};
+
+#[test]
+fn test_details_show_live_state() {
+    let mut hero = Hero::new();
+    hero.info.description = "Brave".into();
+    hero.stat.curr_hp = 5;
+    hero.stat.max_hp = 20;
+    hero.stat.p_def = 20;
+    hero.stat.resistances.fire = -50;
+    hero.stat.resistances.dark = 100;
+    let details = lines(&hero);
+    assert_eq!(details[0], "Brave");
+    let hp = format!("HP {}{} 5/20", "\u{2588}".repeat(5), "\u{2591}".repeat(15));
+    assert!(details.contains(&hp));
+    assert!(details.contains(&"Effects: none".to_owned()));
+    assert!(details.contains(&"  nothing worn".to_owned()));
+    assert!(details.contains(&"weak to fire, immune to dark".to_owned()));
+    assert!(details.iter().any(|l| l.starts_with("crit_chance 0  crit_damage 50")));
+
+    hero.status.apply_effect(Box::new(Burn { damage: 1, duration: 2 }), &mut hero.stat);
+    let details = lines(&hero);
+    assert!(details.iter().any(|l| l.contains("p_def 15 (-5)")));
+    assert!(details.contains(&"  Burn (2 turns left)".to_owned()));
+}
@@ -1,9 +1,10 @@
-use std::rc::Rc;
+use std::{cell::RefCell, marker::PhantomData, rc::Rc};
 
 use crossterm::event::KeyCode;
 use ratatui::{
     layout::Rect,
-    style::{Modifier, Style, Stylize},
+    style::{Color, Modifier, Style, Stylize},
+    text::Line,
     widgets::{Block, Borders, List, ListItem},
     Frame,
 };
@@ -11,31 +12,39 @@ use ratatui::{
 use crate::{
     core::{
         consts::{ACCENT, PRIMARY},
-        entities::{Entity, Id},
+        entities::Id,
+        world::{Member, SharedWorld},
     },
     Event,
 };
 
-use super::{Component, ComponentType};
+use super::Component;
 
+// one side of the world, read fresh every time so it never goes stale
 #[derive(Debug)]
-pub struct EntityList<E: Entity> {
-    pub entities: Vec<Rc<E>>,
+pub struct EntityList<E: Member> {
+    world: SharedWorld,
     enter: bool,
     selected_item_idx: usize,
     turn: Option<Id>,
+    side: PhantomData<E>,
 }
 
-impl<E: Entity> EntityList<E> {
-    pub fn new() -> Self {
+impl<E: Member> EntityList<E> {
+    pub fn new(world: SharedWorld) -> Self {
         Self {
-            entities: Vec::new(),
+            world,
             enter: false,
             selected_item_idx: 0,
             turn: None,
+            side: PhantomData,
         }
     }
 
+    pub fn entities(&self) -> Vec<Rc<RefCell<E>>> {
+        E::roster(&self.world.borrow())
+    }
+
     fn select_up(&mut self) {
         if self.selected_item_idx > 0 {
             self.selected_item_idx -= 1;
@@ -43,30 +52,45 @@ impl<E: Entity> EntityList<E> {
     }
 
     fn select_down(&mut self) {
-        if self.selected_item_idx < self.entities.len() - 1 {
+        if self.selected_item_idx + 1 < self.entities().len() {
             self.selected_item_idx += 1;
         }
     }
 
+    // entities can be replaced or removed from outside, keep the selection on the list
+    fn clamp_selection(&mut self) {
+        self.selected_item_idx = self.selected_item_idx.min(self.entities().len().saturating_sub(1));
+    }
+
+    pub fn select(&mut self, idx: usize) {
+        if idx < self.entities().len() {
+            self.selected_item_idx = idx;
+        }
+    }
+
+    pub fn selected(&self) -> Option<Rc<RefCell<E>>> {
+        let mut entities = self.entities();
+        if self.selected_item_idx < entities.len() {
+            Some(entities.swap_remove(self.selected_item_idx))
+        } else {
+            entities.pop()
+        }
+    }
+
     pub fn set_turn(&mut self, id: Option<Id>) {
-        self.turn = id.and_then(|id| self.entities.iter().find(|e| e.id() == id).map(|e| e.id()));
+        self.turn = id.filter(|id| self.entities().iter().any(|e| e.borrow().id() == *id));
     }
 }
 
-impl<E: Entity> Component for EntityList<E> {
+impl<E: Member> Component for EntityList<E> {
     fn handle_event(&mut self, event: &Event) {
-        match event {
-            Event::Key(k) => match k.code {
-                KeyCode::Char(c) => match c {
-                    'k' => self.select_up(),
-                    'j' => self.select_down(),
-                    _ => (),
-                },
-                KeyCode::Up => self.select_up(),
-                KeyCode::Down => self.select_down(),
+        self.clamp_selection();
+        if let Event::Key(k) = event {
+            match k.code {
+                KeyCode::Char('k') | KeyCode::Up => self.select_up(),
+                KeyCode::Char('j') | KeyCode::Down => self.select_down(),
                 _ => (),
-            },
-            _ => (),
+            }
         }
     }
 
@@ -85,31 +109,59 @@ impl<E: Entity> Component for EntityList<E> {
             .add_modifier(Modifier::UNDERLINED)
             .underline_color(PRIMARY);
         let selected_item_style = Style::default().reversed();
+        self.clamp_selection();
         frame.render_widget(
             List::new(
-                self.entities
+                self.entities()
                     .iter()
                     .enumerate()
                     .map(|(i, e)| {
-                        ListItem::new(format!(
-                            "{} ({}{})",
+                        let e = e.borrow();
+                        let mut lines = vec![Line::from(format!(
+                            "{}{}{} ({}{}{}){}",
+                            if e.is_alive() { "" } else { "\u{271D} " },
                             e.info().name,
+                            e.progress()
+                                .map(|p| format!(" Lv{}", p.level))
+                                .unwrap_or_default(),
                             e.stat().curr_hp,
-                            String::from_utf8(vec![0xE2, 0x99, 0xA5]).unwrap()
-                        ))
-                        .style(
-                            match (
-                                i == self.selected_item_idx,
-                                selected,
-                                self.enter,
-                                self.turn.map_or(false, |id| id == e.id()),
-                            ) {
-                                (true, true, true, true) => selected_turn_style,
-                                (true, true, true, false) => selected_item_style,
-                                (_, _, _, true) => turn_style,
-                                _ => default_style,
+                            String::from_utf8(vec![0xE2, 0x99, 0xA5]).unwrap(),
+                            if e.stat().max_mp > 0 {
+                                format!(" {}mp", e.stat().curr_mp)
+                            } else {
+                                String::new()
                             },
-                        )
+                            e.status()
+                                .effects()
+                                .iter()
+                                .map(|effect| format!(" {}:{}", effect.name(), effect.duration()))
+                                .collect::<String>()
+                        ))];
+                        // the rest lives in the details panel
+                        if self.enter && i == self.selected_item_idx {
+                            let stat = e.effective_stat();
+                            lines.push(Line::from(format!(
+                                "  atk {}/{} def {}/{} spd {}",
+                                stat.p_att, stat.m_att, stat.p_def, stat.m_def, stat.spd
+                            )));
+                        }
+                        let style = match (
+                            i == self.selected_item_idx,
+                            selected,
+                            self.enter,
+                            self.turn == Some(e.id()),
+                        ) {
+                            (true, true, true, true) => selected_turn_style,
+                            (true, true, true, false) => selected_item_style,
+                            (_, _, _, true) => turn_style,
+                            _ => default_style,
+                        };
+                        // the dead stay in the list, greyed out
+                        ListItem::new(lines).style(if e.is_alive() {
+                            style
+                        } else {
+                            style.fg(Color::DarkGray)
+                        })
                     })
                     .collect::<Vec<ListItem>>(),
             )
@@ -124,10 +176,6 @@ impl<E: Entity> Component for EntityList<E> {
         );
     }
 
-    fn get_type(&self) -> ComponentType {
-        ComponentType::EntityList
-    }
-
     fn enter(&mut self) {
         self.enter = true;
     }
@@ -140,3 +188,40 @@ impl<E: Entity> Component for EntityList<E> {
         self.enter = false;
     }
 }
+
+#[cfg(test)]
+use crossterm::event::{KeyEvent, KeyModifiers};
+
+#[cfg(test)]
+use crate::core::{
+    entities::{enemy::Enemy, Entity},
+    world::World,
+};
+
+#[test]
+fn test_selection_is_safe_on_empty_and_shrinking_lists() {
+    let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+    let world = World::shared();
+    let mut list: EntityList<Enemy> = EntityList::new(world.clone());
+    list.handle_event(&key(KeyCode::Down));
+    list.handle_event(&key(KeyCode::Up));
+    assert!(list.selected().is_none());
+
+    let enemies: Vec<Enemy> = (0..3).map(|_| Enemy::new()).collect();
+    let last = enemies[2].id();
+    world.borrow_mut().set_enemies(enemies);
+    list.select(2);
+    list.handle_event(&key(KeyCode::Down));
+    assert_eq!(list.selected().unwrap().borrow().id(), last);
+
+    // the list follows the world, a smaller wave keeps the selection in range
+    let only = Enemy::new();
+    let only_id = only.id();
+    world.borrow_mut().set_enemies(vec![only]);
+    assert_eq!(list.selected().unwrap().borrow().id(), only_id);
+    list.handle_event(&key(KeyCode::Up));
+    assert_eq!(list.selected().unwrap().borrow().id(), only_id);
+    world.borrow_mut().set_enemies(Vec::new());
+    list.handle_event(&key(KeyCode::Down));
+    assert!(list.selected().is_none());
+}
@@ -0,0 +1,152 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem},
+    Frame,
+};
+
+use crate::{
+    core::{
+        consts::{ACCENT, PRIMARY},
+        data::{Catalog, ItemDefinition},
+        entities::hero::Hero,
+        inventory::Inventory,
+        types::equipment::StatKind,
+    },
+    Event,
+};
+
+use super::Component;
+
+#[derive(Debug)]
+pub struct InventoryView {
+    pub inventory: Inventory,
+    catalog: Rc<Catalog>,
+    // who the stat previews are for
+    hero: Option<Rc<RefCell<Hero>>>,
+    selected_item_idx: usize,
+    enter: bool,
+}
+
+impl InventoryView {
+    pub fn new() -> Self {
+        Self {
+            inventory: Inventory::default(),
+            catalog: Rc::default(),
+            hero: None,
+            selected_item_idx: 0,
+            enter: false,
+        }
+    }
+
+    pub fn set_catalog(&mut self, catalog: Rc<Catalog>) {
+        self.catalog = catalog;
+    }
+
+    pub fn set_hero(&mut self, hero: Option<Rc<RefCell<Hero>>>) {
+        self.hero = hero;
+    }
+
+    pub fn selected(&self) -> Option<&ItemDefinition> {
+        let stack = self.inventory.stacks().get(self.selected_item_idx)?;
+        self.catalog.item(&stack.item).ok()
+    }
+
+    // what wearing the item would change for the hero, before -> after
+    fn preview(&self, item: &ItemDefinition) -> Vec<String> {
+        let (Some(gear), Some(hero)) = (item.gear(), &self.hero) else {
+            return Vec::new();
+        };
+        let hero = hero.borrow();
+        let mut lines = vec![match hero.equipment.get(gear.slot) {
+            Some(old) => format!("{} for {}, replaces {}", gear.slot, hero.info.name, old.name),
+            None => format!("{} for {}", gear.slot, hero.info.name),
+        }];
+        let before = hero.status.modified(&hero.equipment.modified(&hero.stat));
+        let after = hero.status.modified(&hero.equipment.with(gear).modified(&hero.stat));
+        lines.extend(StatKind::ALL.iter().filter_map(|kind| {
+            let (before, after) = (kind.get(&before), kind.get(&after));
+            (before != after).then(|| format!("{} {} -> {} ({:+})", kind.name(), before, after, after - before))
+        }));
+        lines
+    }
+
+    fn select_up(&mut self) {
+        self.selected_item_idx = self.selected_item_idx.saturating_sub(1);
+    }
+
+    fn select_down(&mut self) {
+        if self.selected_item_idx + 1 < self.inventory.stacks().len() {
+            self.selected_item_idx += 1;
+        }
+    }
+}
+
+impl Component for InventoryView {
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Key(k) = event {
+            match k.code {
+                KeyCode::Char('k') | KeyCode::Up => self.select_up(),
+                KeyCode::Char('j') | KeyCode::Down => self.select_down(),
+                _ => (),
+            }
+        }
+    }
+
+    fn render(&mut self, title: &str, frame: &mut Frame, area: Rect, selected: bool) {
+        let color = if selected { ACCENT } else { PRIMARY };
+        let stacks = self.inventory.stacks();
+        self.selected_item_idx = self.selected_item_idx.min(stacks.len().saturating_sub(1));
+        let items: Vec<ListItem> = stacks
+            .iter()
+            .enumerate()
+            .map(|(i, stack)| {
+                let item = self.catalog.item(&stack.item).ok();
+                let name = item.map_or(stack.item.as_str(), |item| item.name.as_str());
+                let mut lines = vec![Line::from(if stack.count > 1 {
+                    format!("{} x{}", name, stack.count)
+                } else {
+                    name.to_owned()
+                })];
+                if self.enter && i == self.selected_item_idx {
+                    if let Some(item) = item {
+                        lines.push(Line::from(format!("  {}", item.description)));
+                        lines.push(Line::from(format!("  worth {} gold", item.value)));
+                        lines.extend(self.preview(item).into_iter().map(|l| Line::from(format!("  {}", l))));
+                    }
+                    return ListItem::new(lines).style(Style::default().reversed());
+                }
+                ListItem::new(lines)
+            })
+            .collect();
+        let title = format!(
+            "{}{} gold, {}/{} ",
+            title,
+            self.inventory.gold,
+            stacks.len(),
+            self.inventory.capacity
+        );
+        frame.render_widget(
+            List::new(items)
+                .fg(color)
+                .block(Block::default().borders(Borders::ALL).fg(color).title(title)),
+            area,
+        );
+    }
+
+    fn enter(&mut self) {
+        self.enter = true;
+    }
+
+    fn is_entered(&self) -> bool {
+        self.enter
+    }
+
+    fn exit(&mut self) {
+        self.enter = false;
+    }
+}
@@ -0,0 +1,131 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    widgets::{Block, Borders, Clear, List, ListItem},
+    Frame,
+};
+
+use crate::{
+    core::consts::{ACCENT, PRIMARY},
+    Event,
+};
+
+use super::Component;
+
+// a short list of choices drawn on top of whatever is under it
+#[derive(Debug)]
+pub struct Menu {
+    items: Vec<String>,
+    selected_item_idx: usize,
+    enter: bool,
+}
+
+impl Menu {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            selected_item_idx: 0,
+            enter: false,
+        }
+    }
+
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.selected_item_idx = 0;
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        (self.selected_item_idx < self.items.len()).then_some(self.selected_item_idx)
+    }
+
+    fn select_up(&mut self) {
+        self.selected_item_idx = self.selected_item_idx.saturating_sub(1);
+    }
+
+    fn select_down(&mut self) {
+        if self.selected_item_idx + 1 < self.items.len() {
+            self.selected_item_idx += 1;
+        }
+    }
+}
+
+impl Component for Menu {
+    fn handle_event(&mut self, event: &Event) {
+        if let Event::Key(k) = event {
+            match k.code {
+                KeyCode::Char('k') | KeyCode::Up => self.select_up(),
+                KeyCode::Char('j') | KeyCode::Down => self.select_down(),
+                _ => (),
+            }
+        }
+    }
+
+    fn render(&mut self, title: &str, frame: &mut Frame, area: Rect, selected: bool) {
+        let color = if selected { ACCENT } else { PRIMARY };
+        let width = self
+            .items
+            .iter()
+            .map(|item| item.chars().count())
+            .chain([title.chars().count()])
+            .max()
+            .unwrap_or(0) as u16
+            + 4;
+        let height = self.items.len() as u16 + 2;
+        let area = Rect {
+            x: area.x + area.width.saturating_sub(width) / 2,
+            y: area.y + area.height.saturating_sub(height) / 2,
+            width: width.min(area.width),
+            height: height.min(area.height),
+        };
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            List::new(
+                self.items
+                    .iter()
+                    .enumerate()
+                    .map(|(i, item)| {
+                        let item = ListItem::new(format!(" {}", item));
+                        if i == self.selected_item_idx && self.enter {
+                            item.style(Style::default().reversed())
+                        } else {
+                            item
+                        }
+                    })
+                    .collect::<Vec<ListItem>>(),
+            )
+            .fg(color)
+            .block(Block::default().borders(Borders::ALL).fg(color).title(title)),
+            area,
+        );
+    }
+
+    fn enter(&mut self) {
+        self.enter = true;
+    }
+
+    fn is_entered(&self) -> bool {
+        self.enter
+    }
+
+    fn exit(&mut self) {
+        self.enter = false;
+    }
+}
+
+#[cfg(test)]
+use crossterm::event::{KeyEvent, KeyModifiers};
+
+#[test]
+fn test_menu_selection_stays_in_bounds() {
+    let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+    let mut menu = Menu::new();
+    menu.handle_event(&key(KeyCode::Down));
+    assert_eq!(menu.selected(), None);
+    menu.set_items(vec!["Attack".to_owned(), "Heal".to_owned()]);
+    menu.handle_event(&key(KeyCode::Up));
+    assert_eq!(menu.selected(), Some(0));
+    menu.handle_event(&key(KeyCode::Char('j')));
+    menu.handle_event(&key(KeyCode::Char('j')));
+    assert_eq!(menu.selected(), Some(1));
+}
@@ -6,6 +6,15 @@ pub use entity_list::EntityList;
 pub mod command;
 pub use command::Command;
 
+pub mod menu;
+pub use menu::Menu;
+
+pub mod inventory;
+pub use inventory::InventoryView;
+
+pub mod details;
+pub use details::Details;
+
 pub mod turn;
 use ratatui::{Frame, layout::Rect};
 pub use turn::Turn;
@@ -18,12 +27,5 @@ pub trait Component: Debug {
     fn enter(&mut self);
     fn is_entered(&self) -> bool;
     fn exit(&mut self);
-    fn get_type(&self) -> ComponentType;
-}
-
-pub enum ComponentType {
-    EntityList,
-    Command,
-    Turn,
 }
 
@@ -1,5 +1,3 @@
-use std::{collections::VecDeque, ops::Deref, rc::Rc};
-
 use crossterm::event::KeyCode;
 use ratatui::{
     layout::{Margin, Rect},
@@ -13,86 +11,35 @@ use ratatui::{
 use crate::{
     core::{
         consts::{ACCENT, PRIMARY},
-        entities::{Entity, Id},
+        timeline::SharedTimeline,
     },
     Event,
 };
 
-use super::{Component, ComponentType};
+use super::Component;
+
+// how many upcoming actions the pane shows
+const PREVIEW: usize = 12;
 
+// draws the timeline, the engine itself lives in core::timeline
 #[derive(Debug)]
 pub struct Turn {
     selected_item_idx: usize,
     paragraph_offset: usize,
-    current_round_order: VecDeque<Id>,
-    next_round_order: Vec<Rc<dyn Entity>>,
+    timeline: SharedTimeline,
     enter: bool,
 }
 
 impl Turn {
-    pub fn new() -> Self {
+    pub fn new(timeline: SharedTimeline) -> Self {
         Self {
             selected_item_idx: 0,
             paragraph_offset: 0,
-            current_round_order: VecDeque::new(),
-            next_round_order: Vec::new(),
+            timeline,
             enter: false,
         }
     }
 
-    pub fn set_entities(&mut self, next_round_order: Vec<Rc<dyn Entity>>) {
-        self.next_round_order = next_round_order;
-        self.next_round_order
-            .sort_by(|a, b| a.stat().spd.cmp(&b.stat().spd));
-        self.current_round_order = self.next_round_order.iter().map(|e| e.id()).collect();
-    }
-
-    pub fn update_next_round_order(&mut self) {
-        self.next_round_order
-            .sort_by(|a, b| a.stat().spd.cmp(&b.stat().spd));
-    }
-
-    pub fn pop(&mut self) -> Option<Id> {
-        if self.current_round_order.len() == 1 {
-            self.goto_next_round();
-            self.get_current_turn()
-        } else {
-            self.current_round_order.pop_front();
-            let last_idx = self.current_round_order.len() + self.next_round_order.len() - 1;
-            if self.selected_item_idx > last_idx {
-                self.selected_item_idx = last_idx;
-            } else {
-                self.select_up();
-            }
-            if self.selected_item_idx < self.paragraph_offset {
-                self.paragraph_offset -= 1;
-            } else if self.selected_item_idx - self.paragraph_offset >= 3 {
-                self.paragraph_offset += 1;
-            }
-            self.current_round_order.get(0).copied()
-        }
-    }
-
-    pub fn get_current_turn(&self) -> Option<Id> {
-        self.current_round_order.get(0).copied()
-    }
-
-    pub fn goto_next_round(&mut self) {
-        self.current_round_order.pop_front();
-        self.current_round_order = self.next_round_order.iter().map(|e| e.id()).collect();
-        let last_idx = self.current_round_order.len() + self.next_round_order.len();
-        if self.selected_item_idx > last_idx {
-            self.selected_item_idx = last_idx;
-        } else {
-            self.select_up();
-        }
-        if self.selected_item_idx < self.paragraph_offset {
-            self.paragraph_offset -= 1;
-        } else if self.selected_item_idx - self.paragraph_offset >= 3 {
-            self.paragraph_offset += 1;
-        }
-    }
-
     fn select_up(&mut self) {
         if self.selected_item_idx > 0 {
             self.selected_item_idx -= 1;
@@ -100,7 +47,7 @@ impl Turn {
     }
 
     fn select_down(&mut self) {
-        if self.selected_item_idx < self.current_round_order.len() + self.next_round_order.len() - 1 {
+        if self.selected_item_idx + 1 < self.timeline.borrow().projection(PREVIEW).len() {
             self.selected_item_idx += 1;
         }
     }
@@ -108,51 +55,46 @@ impl Turn {
 
 impl Component for Turn {
     fn handle_event(&mut self, event: &Event) {
-        match event {
-            Event::Key(k) => match k.code {
-                KeyCode::Char(c) => match c {
-                    'k' => self.select_up(),
-                    'j' => self.select_down(),
-                    _ => (),
-                }
-                KeyCode::Up => self.select_up(),
-                KeyCode::Down => self.select_down(),
+        if let Event::Key(k) = event {
+            match k.code {
+                KeyCode::Char('k') | KeyCode::Up => self.select_up(),
+                KeyCode::Char('j') | KeyCode::Down => self.select_down(),
                 _ => (),
-            },
-            _ => (),
-        }
-        if self.selected_item_idx < self.paragraph_offset {
-            self.paragraph_offset -= 1;
-        } else if self.selected_item_idx - self.paragraph_offset >= 3 {
-            self.paragraph_offset += 1;
+            }
         }
     }
 
     fn render(&mut self, title: &str, frame: &mut Frame, area: Rect, selected: bool) {
         let color = if selected { ACCENT } else { PRIMARY };
-        let entts_name: Vec<&str> = self
-            .current_round_order
-            .iter()
-            .map(|id| {
-                self.next_round_order
-                    .iter()
-                    .find(|e| e.id() == *id)
-                    .unwrap()
-                    .info()
-                    .name
-                    .deref()
+        let timeline = self.timeline.borrow();
+        let world = timeline.world().borrow();
+        let lines: Vec<String> = timeline
+            .projection(PREVIEW)
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, (id, gap))| {
+                let entity = world.get(id)?;
+                let gap = if i == 0 { "now".to_owned() } else { format!("+{}", gap) };
+                Some(format!("{:>5} {}", gap, entity.borrow().info().name))
             })
-            .chain(self.next_round_order.iter().map(|e| e.info().name.deref()))
             .collect();
+        self.selected_item_idx = self.selected_item_idx.min(lines.len().saturating_sub(1));
+        // keep the selected line inside the pane
+        let visible = area.height.saturating_sub(2).max(1) as usize;
+        if self.selected_item_idx < self.paragraph_offset {
+            self.paragraph_offset = self.selected_item_idx;
+        } else if self.selected_item_idx >= self.paragraph_offset + visible {
+            self.paragraph_offset = self.selected_item_idx + 1 - visible;
+        }
         let paragraph = Paragraph::new(
-            entts_name
+            lines
                 .iter()
                 .enumerate()
-                .map(|(i, name)| {
+                .map(|(i, line)| {
                     if self.selected_item_idx == i && self.enter {
-                        Line::styled(*name, Style::default().reversed())
+                        Line::styled(line.as_str(), Style::default().reversed())
                     } else {
-                        Line::from(*name)
+                        Line::from(line.as_str())
                     }
                 })
                 .collect::<Vec<_>>(),
@@ -173,9 +115,7 @@ impl Component for Turn {
             //.end_symbol(Some("↓"));
             .begin_symbol(None)
             .end_symbol(None);
-        let mut scrollbar_state =
-            ScrollbarState::new(self.current_round_order.len() + self.next_round_order.len())
-                .position(self.selected_item_idx);
+        let mut scrollbar_state = ScrollbarState::new(lines.len()).position(self.selected_item_idx);
 
         frame.render_stateful_widget(
             scrollbar,
@@ -198,10 +138,18 @@ impl Component for Turn {
     fn exit(&mut self) {
         self.enter = false;
     }
+}
 
-    fn get_type(&self) -> ComponentType {
-        ComponentType::Turn
-    }
+#[cfg(test)]
+use crate::core::{timeline::Timeline, world::World};
+
+#[test]
+fn test_selection_stays_inside_the_preview() {
+    let mut turn = Turn::new(Timeline::shared(World::shared()));
+    turn.select_down();
+    assert_eq!(turn.selected_item_idx, 0);
+    turn.select_up();
+    assert_eq!(turn.selected_item_idx, 0);
 }
 
 #[test]
@@ -1,4 +1,8 @@
+use std::time::Duration;
+
 use ratatui::style::Color;
 
 pub const PRIMARY: Color = Color::Rgb(255, 0, 255);
 pub const ACCENT: Color = Color::Rgb(76, 248, 153);
+
+pub const TURN_DELAY: Duration = Duration::from_secs(2);
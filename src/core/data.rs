@@ -0,0 +1,533 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{
+    entities::Strategy,
+    save::data_dir,
+    types::{equipment::Modifier, BasicStat, Element, Gear, Growth, Skill, SkillSlot, Slot},
+};
+
+const CLASSES: &str = include_str!("../../data/classes.json");
+const ENEMIES: &str = include_str!("../../data/enemies.json");
+const SKILLS: &str = include_str!("../../data/skills.json");
+const ITEMS: &str = include_str!("../../data/items.json");
+
+// loot tables can name gold like an item, it goes to the purse instead of the inventory
+pub const GOLD: &str = "gold";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillDefinition {
+    pub id: String,
+    pub skill: Box<dyn Skill>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemDefinition {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default = "ItemDefinition::default_stack")]
+    pub max_stack: u32,
+    // in gold
+    #[serde(default)]
+    pub value: u64,
+    // only items with a slot can be worn
+    #[serde(default)]
+    pub slot: Option<Slot>,
+    #[serde(default)]
+    pub modifiers: Vec<Modifier>,
+    #[serde(default)]
+    pub element: Option<Element>,
+}
+
+impl ItemDefinition {
+    fn default_stack() -> u32 {
+        1
+    }
+
+    pub fn gear(&self) -> Option<Gear> {
+        Some(Gear {
+            item: self.id.clone(),
+            name: self.name.clone(),
+            slot: self.slot?,
+            modifiers: self.modifiers.clone(),
+            element: self.element,
+        })
+    }
+}
+
+// a misspelled key would otherwise just fall back to its default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HeroClass {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub image_path: String,
+    pub stats: BasicStat,
+    #[serde(default)]
+    pub growth: Growth,
+    #[serde(default)]
+    pub skills: Vec<String>,
+    // part of the party a new game starts with
+    #[serde(default)]
+    pub starter: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EnemyArchetype {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub image_path: String,
+    pub stats: BasicStat,
+    #[serde(default)]
+    pub growth: Growth,
+    #[serde(default)]
+    pub skills: Vec<String>,
+    #[serde(default)]
+    pub strategy: Strategy,
+    // only shows up in boss waves
+    #[serde(default)]
+    pub boss: bool,
+    #[serde(default)]
+    pub loot: Vec<LootEntry>,
+    // what its basic attacks deal, like a hero's weapon
+    #[serde(default)]
+    pub element: Option<Element>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LootEntry {
+    pub item: String,
+    pub weight: u32,
+}
+
+// every kind of definition goes through the same id checks
+trait Definition {
+    const KIND: &'static str;
+    fn id(&self) -> &str;
+    fn problem(&self) -> Option<&'static str>;
+    fn skills(&self) -> &[String] {
+        &[]
+    }
+}
+
+fn entity_problem(name: &str, stats: &BasicStat, growth: &Growth, skills: &[String]) -> Option<&'static str> {
+    if name.trim().is_empty() {
+        return Some("name can't be empty");
+    }
+    if stats.max_hp == 0 {
+        return Some("max_hp must be above 0");
+    }
+    if [stats.p_att, stats.m_att, stats.p_def, stats.m_def].iter().any(|s| *s < 0) {
+        return Some("attack and defense can't be negative");
+    }
+    if ![stats.crit_chance, stats.accuracy, stats.evasion].iter().all(|s| (0..=100).contains(s)) {
+        return Some("crit_chance, accuracy and evasion must be between 0 and 100");
+    }
+    if stats.crit_damage < 0 {
+        return Some("crit_damage can't be negative");
+    }
+    if !Element::ALL.iter().all(|e| (-100..=100).contains(&stats.resistances.get(*e))) {
+        return Some("resistances must be between -100 and 100");
+    }
+    let rates = [
+        growth.p_att,
+        growth.m_att,
+        growth.p_def,
+        growth.m_def,
+        growth.max_hp,
+        growth.max_mp,
+        growth.spd,
+    ];
+    if !rates.iter().all(|r| r.is_finite() && *r >= 0.) {
+        return Some("growth rates must be positive numbers");
+    }
+    if !(growth.curve.is_finite() && growth.curve > 0.) {
+        return Some("growth curve must be above 0");
+    }
+    if skills.iter().any(|s| s.is_empty()) {
+        return Some("skill names can't be empty");
+    }
+    None
+}
+
+impl Definition for HeroClass {
+    const KIND: &'static str = "class";
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn problem(&self) -> Option<&'static str> {
+        entity_problem(&self.name, &self.stats, &self.growth, &self.skills)
+    }
+    fn skills(&self) -> &[String] {
+        &self.skills
+    }
+}
+
+impl Definition for EnemyArchetype {
+    const KIND: &'static str = "enemy";
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn problem(&self) -> Option<&'static str> {
+        if self.loot.iter().any(|l| l.item.is_empty() || l.weight == 0) {
+            return Some("loot entries need an item and a weight above 0");
+        }
+        entity_problem(&self.name, &self.stats, &self.growth, &self.skills)
+    }
+    fn skills(&self) -> &[String] {
+        &self.skills
+    }
+}
+
+impl Definition for ItemDefinition {
+    const KIND: &'static str = "item";
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn problem(&self) -> Option<&'static str> {
+        if self.name.trim().is_empty() {
+            return Some("name can't be empty");
+        }
+        if self.max_stack == 0 {
+            return Some("max_stack must be above 0");
+        }
+        if self.id == GOLD {
+            return Some("gold is reserved for currency");
+        }
+        if self.slot.is_none() && !self.modifiers.is_empty() {
+            return Some("modifiers need a slot to be worn in");
+        }
+        if self.slot.is_some() && self.max_stack > 1 {
+            return Some("equipment can't stack");
+        }
+        if self.element.is_some() && self.slot != Some(Slot::Weapon) {
+            return Some("only weapons can have an element");
+        }
+        None
+    }
+}
+
+impl Definition for SkillDefinition {
+    const KIND: &'static str = "skill";
+    fn id(&self) -> &str {
+        &self.id
+    }
+    fn problem(&self) -> Option<&'static str> {
+        if self.skill.name().trim().is_empty() {
+            return Some("name can't be empty");
+        }
+        if !(self.skill.power().is_finite() && self.skill.power() >= 0.) {
+            return Some("power must be a positive number");
+        }
+        None
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Catalog {
+    pub classes: Vec<HeroClass>,
+    pub enemies: Vec<EnemyArchetype>,
+    pub skills: Vec<SkillDefinition>,
+    pub items: Vec<ItemDefinition>,
+}
+
+impl Catalog {
+    pub fn user_dir() -> PathBuf {
+        data_dir().join("data")
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(Some(&Self::user_dir()))
+    }
+
+    #[cfg(test)]
+    pub fn bundled() -> Self {
+        Self::load_from(None).expect("bundled data is valid")
+    }
+
+    // bundled definitions first, then the user's files override by id or add new ones
+    pub fn load_from(dir: Option<&Path>) -> Result<Self> {
+        let catalog = Self {
+            classes: load_definitions(CLASSES, dir.map(|d| d.join("classes.json")))?,
+            enemies: load_definitions(ENEMIES, dir.map(|d| d.join("enemies.json")))?,
+            skills: load_definitions(SKILLS, dir.map(|d| d.join("skills.json")))?,
+            items: load_definitions(ITEMS, dir.map(|d| d.join("items.json")))?,
+        };
+        catalog.check_skills(&catalog.classes)?;
+        catalog.check_skills(&catalog.enemies)?;
+        for enemy in &catalog.enemies {
+            if let Some(loot) = enemy.loot.iter().find(|l| l.item != GOLD && catalog.item(&l.item).is_err()) {
+                bail!("enemy '{}': unknown loot item '{}'", enemy.id, loot.item);
+            }
+        }
+        if !catalog.classes.iter().any(|c| c.starter) {
+            bail!("classes: at least one class has to be a starter");
+        }
+        // bosses only come with the last wave of a stage, everything else needs regulars
+        if catalog.enemies.iter().all(|e| e.boss) {
+            bail!("enemies: at least one enemy has to be a regular, not a boss");
+        }
+        Ok(catalog)
+    }
+
+    fn check_skills<T: Definition>(&self, definitions: &[T]) -> Result<()> {
+        for d in definitions {
+            if let Some(skill) = d.skills().iter().find(|s| self.skill(s).is_none()) {
+                bail!("{} '{}': unknown skill '{}'", T::KIND, d.id(), skill);
+            }
+        }
+        Ok(())
+    }
+
+    fn skill(&self, id: &str) -> Option<&dyn Skill> {
+        self.skills.iter().find(|s| s.id == id).map(|s| &*s.skill)
+    }
+
+    // fresh slots for an entity, ids were checked when loading
+    pub fn skills(&self, ids: &[String]) -> Vec<SkillSlot> {
+        ids.iter()
+            .filter_map(|id| self.skill(id))
+            .map(|skill| SkillSlot::new(skill.boxed_clone()))
+            .collect()
+    }
+
+    pub fn class(&self, id: &str) -> Result<&HeroClass> {
+        self.classes
+            .iter()
+            .find(|c| c.id == id)
+            .ok_or_else(|| anyhow!("unknown class '{}'", id))
+    }
+
+    pub fn item(&self, id: &str) -> Result<&ItemDefinition> {
+        self.items
+            .iter()
+            .find(|i| i.id == id)
+            .ok_or_else(|| anyhow!("unknown item '{}'", id))
+    }
+
+    pub fn archetype(&self, id: &str) -> Result<&EnemyArchetype> {
+        self.enemies
+            .iter()
+            .find(|e| e.id == id)
+            .ok_or_else(|| anyhow!("unknown enemy '{}'", id))
+    }
+}
+
+fn parse<T: DeserializeOwned>(content: &str, source: &str) -> Result<Vec<T>> {
+    serde_json::from_str(content).with_context(|| format!("cannot parse {}", source))
+}
+
+fn load_definitions<T: Definition + DeserializeOwned>(
+    bundled: &str,
+    user: Option<PathBuf>,
+) -> Result<Vec<T>> {
+    let mut definitions: Vec<T> = parse(bundled, "bundled data")?;
+    validate(&definitions, "bundled data")?;
+    let Some(user) = user else {
+        return Ok(definitions);
+    };
+    let content = match fs::read_to_string(&user) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(definitions),
+        Err(e) => return Err(e).with_context(|| format!("cannot read {}", user.display())),
+    };
+    let source = user.display().to_string();
+    let overrides: Vec<T> = parse(&content, &source)?;
+    validate(&overrides, &source)?;
+    for definition in overrides {
+        match definitions.iter_mut().find(|d| d.id() == definition.id()) {
+            Some(existing) => *existing = definition,
+            None => definitions.push(definition),
+        }
+    }
+    Ok(definitions)
+}
+
+fn validate<T: Definition>(definitions: &[T], source: &str) -> Result<()> {
+    if definitions.is_empty() {
+        bail!("{}: no {} defined", source, T::KIND);
+    }
+    for (i, d) in definitions.iter().enumerate() {
+        let check = |ok: bool, problem: &str| -> Result<()> {
+            if ok {
+                Ok(())
+            } else {
+                Err(anyhow!("{}: {} '{}': {}", source, T::KIND, d.id(), problem))
+            }
+        };
+        check(!d.id().is_empty(), "id can't be empty")?;
+        check(
+            d.id().chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_'),
+            "id must be lowercase letters, digits or '_'",
+        )?;
+        check(
+            !definitions[..i].iter().any(|other| other.id() == d.id()),
+            "defined twice",
+        )?;
+        if let Some(problem) = d.problem() {
+            bail!("{}: {} '{}': {}", source, T::KIND, d.id(), problem);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+use crate::testing::TestDir;
+
+#[cfg(test)]
+fn test_dir(name: &str) -> TestDir {
+    TestDir::new(&format!("data-{}", name))
+}
+
+#[test]
+fn test_bundled_data_is_valid() {
+    let catalog = Catalog::load_from(Some(&test_dir("bundled"))).unwrap();
+    assert!(catalog.class("warrior").is_ok());
+    assert!(catalog.archetype("goblin").is_ok());
+    assert!(catalog.class("dragon").is_err());
+}
+
+#[test]
+fn test_user_files_override_and_extend() {
+    let dir = test_dir("override");
+    fs::write(
+        dir.join("enemies.json"),
+        r#"[
+            { "id": "goblin", "name": "Hobgoblin", "stats": { "max_hp": 40 } },
+            { "id": "dragon", "name": "Dragon", "stats": { "max_hp": 200 } }
+        ]"#,
+    )
+    .unwrap();
+    let catalog = Catalog::load_from(Some(&dir)).unwrap();
+    let goblin = catalog.archetype("goblin").unwrap();
+    assert_eq!(goblin.name, "Hobgoblin");
+    assert_eq!(goblin.stats.max_hp, 40);
+    assert_eq!(catalog.archetype("dragon").unwrap().stats.max_hp, 200);
+    assert_eq!(catalog.enemies.len(), Catalog::bundled().enemies.len() + 1);
+}
+
+#[test]
+fn test_unknown_skills_are_reported() {
+    let dir = test_dir("skills");
+    fs::write(
+        dir.join("classes.json"),
+        r#"[{ "id": "bard", "name": "Bard", "stats": {}, "skills": ["lullaby"] }]"#,
+    )
+    .unwrap();
+    let error = format!("{:#}", Catalog::load_from(Some(&dir)).unwrap_err());
+    assert_eq!(error, "class 'bard': unknown skill 'lullaby'");
+
+    fs::write(
+        dir.join("skills.json"),
+        r#"[{ "id": "lullaby", "skill": { "type": "Enchant", "value": {
+            "name": "Lullaby", "target": "AllEnemies", "cost": 6,
+            "effect": { "type": "Stun", "value": { "duration": 1 } } } } }]"#,
+    )
+    .unwrap();
+    let catalog = Catalog::load_from(Some(&dir)).unwrap();
+    let slots = catalog.skills(&["lullaby".to_owned()]);
+    assert_eq!(slots[0].skill.name(), "Lullaby");
+}
+
+#[test]
+fn test_unknown_loot_is_reported() {
+    let dir = test_dir("loot");
+    fs::write(
+        dir.join("enemies.json"),
+        r#"[{ "id": "imp", "name": "Imp", "stats": {}, "loot": [{ "item": "pitchfork", "weight": 1 }] }]"#,
+    )
+    .unwrap();
+    let error = format!("{:#}", Catalog::load_from(Some(&dir)).unwrap_err());
+    assert_eq!(error, "enemy 'imp': unknown loot item 'pitchfork'");
+
+    fs::write(
+        dir.join("items.json"),
+        r#"[{ "id": "pitchfork", "name": "Pitchfork" }]"#,
+    )
+    .unwrap();
+    let catalog = Catalog::load_from(Some(&dir)).unwrap();
+    assert_eq!(catalog.item("pitchfork").unwrap().max_stack, 1);
+}
+
+#[test]
+fn test_bosses_alone_are_rejected() {
+    let dir = test_dir("bosses");
+    let bosses: Vec<String> = ["goblin", "slime", "skeleton", "wraith"]
+        .iter()
+        .map(|id| format!(r#"{{ "id": "{}", "name": "Boss", "stats": {{}}, "boss": true }}"#, id))
+        .collect();
+    fs::write(dir.join("enemies.json"), format!("[{}]", bosses.join(","))).unwrap();
+    let error = format!("{:#}", Catalog::load_from(Some(&dir)).unwrap_err());
+    assert_eq!(error, "enemies: at least one enemy has to be a regular, not a boss");
+}
+
+#[test]
+fn test_a_catalog_without_starters_is_rejected() {
+    let dir = test_dir("starters");
+    let classes: Vec<String> = Catalog::bundled()
+        .classes
+        .iter()
+        .map(|c| format!(r#"{{ "id": "{}", "name": "{}", "stats": {{}} }}"#, c.id, c.name))
+        .collect();
+    fs::write(dir.join("classes.json"), format!("[{}]", classes.join(","))).unwrap();
+    let error = format!("{:#}", Catalog::load_from(Some(&dir)).unwrap_err());
+    assert_eq!(error, "classes: at least one class has to be a starter");
+}
+
+#[test]
+fn test_invalid_data_is_reported() {
+    let dir = test_dir("invalid");
+    let path = dir.join("classes.json");
+    let error = |content: &str| {
+        fs::write(&path, content).unwrap();
+        format!("{:#}", Catalog::load_from(Some(&dir)).unwrap_err())
+    };
+    assert!(error("[{").contains("cannot parse"));
+    assert!(error(r#"[{ "id": "knight", "name": "Knight", "stats": { "max_hp": 0 } }]"#)
+        .contains("class 'knight': max_hp must be above 0"));
+    assert!(error(r#"[{ "id": "Knight", "name": "Knight", "stats": {} }]"#).contains("lowercase"));
+    assert!(error(
+        r#"[{ "id": "a", "name": "A", "stats": {} }, { "id": "a", "name": "B", "stats": {} }]"#
+    )
+    .contains("defined twice"));
+    assert!(error(r#"[{ "id": "knight", "name": "Knight", "stats": { "evasion": 120 } }]"#)
+        .contains("between 0 and 100"));
+    assert!(error(r#"[{ "id": "knight", "name": "Knight", "stats": { "resistances": { "fire": 150 } } }]"#)
+        .contains("resistances must be between -100 and 100"));
+}
+
+#[test]
+fn test_misspelled_fields_are_rejected() {
+    let dir = test_dir("misspelled");
+    let error = |file: &str, content: &str| {
+        fs::write(dir.join(file), content).unwrap();
+        let error = format!("{:#}", Catalog::load_from(Some(&dir)).unwrap_err());
+        fs::remove_file(dir.join(file)).unwrap();
+        error
+    };
+    assert!(error("classes.json", r#"[{ "id": "knight", "name": "Knight", "stats": {}, "skils": [] }]"#)
+        .contains("unknown field `skils`"));
+    assert!(error("classes.json", r#"[{ "id": "knight", "name": "Knight", "stats": { "max_pH": 30 } }]"#)
+        .contains("unknown field `max_pH`"));
+    assert!(error("enemies.json", r#"[{ "id": "imp", "name": "Imp", "stats": {}, "bos": true }]"#)
+        .contains("unknown field `bos`"));
+    assert!(error("enemies.json", r#"[{ "id": "imp", "name": "Imp", "stats": {}, "growth": { "curv": 2.0 } }]"#)
+        .contains("unknown field `curv`"));
+}
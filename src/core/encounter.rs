@@ -0,0 +1,252 @@
+use std::fmt::Display;
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    data::{Catalog, EnemyArchetype},
+    entities::{enemy::Enemy, Entity, IdAllocator, SharedEntity},
+    inventory::{self, Loot},
+    world::SharedWorld,
+};
+
+pub const WAVES_PER_STAGE: u32 = 3;
+// the last wave of every n-th stage is a boss, beating it moves the checkpoint
+pub const BOSS_EVERY: u32 = 5;
+// share of max hp the survivors get back between waves
+const REST: f32 = 0.2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Victory,
+    Defeat,
+}
+
+pub fn outcome(heroes: &[SharedEntity], enemies: &[SharedEntity]) -> Option<Outcome> {
+    let wiped = |side: &[SharedEntity]| side.iter().all(|e| !e.borrow().is_alive());
+    if wiped(heroes) {
+        Some(Outcome::Defeat)
+    } else if wiped(enemies) {
+        Some(Outcome::Victory)
+    } else {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Encounter {
+    pub stage: u32,
+    pub wave: u32,
+    // where the party retreats to when wiped out
+    pub checkpoint: u32,
+}
+
+impl Default for Encounter {
+    fn default() -> Self {
+        Self {
+            stage: 1,
+            wave: 1,
+            checkpoint: 1,
+        }
+    }
+}
+
+impl Display for Encounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Stage {} - Wave {}/{}", self.stage, self.wave, WAVES_PER_STAGE)?;
+        if self.is_boss_wave() {
+            write!(f, " (boss)")?;
+        }
+        Ok(())
+    }
+}
+
+impl Encounter {
+    pub fn is_boss_wave(&self) -> bool {
+        self.stage.is_multiple_of(BOSS_EVERY) && self.wave == WAVES_PER_STAGE
+    }
+
+    // two levels per stage, one per wave, bosses are a notch above
+    pub fn enemy_level(&self) -> u32 {
+        (self.stage - 1) * 2 + self.wave + if self.is_boss_wave() { 2 } else { 0 }
+    }
+
+    pub fn advance(&mut self) {
+        if self.is_boss_wave() {
+            self.checkpoint = self.stage + 1;
+        }
+        if self.wave >= WAVES_PER_STAGE {
+            self.stage += 1;
+            self.wave = 1;
+        } else {
+            self.wave += 1;
+        }
+    }
+
+    pub fn retreat(&mut self) {
+        self.stage = self.checkpoint;
+        self.wave = 1;
+    }
+
+    // a boss with an escort, or a pack that grows with the wave
+    pub fn spawn(&self, ids: &mut IdAllocator, catalog: &Catalog, rng: &mut impl Rng) -> Vec<Enemy> {
+        let regular: Vec<&EnemyArchetype> = catalog.enemies.iter().filter(|e| !e.boss).collect();
+        let bosses: Vec<&EnemyArchetype> = catalog.enemies.iter().filter(|e| e.boss).collect();
+        let level = self.enemy_level();
+        let mut picks: Vec<(&EnemyArchetype, u32)> = Vec::new();
+        if self.is_boss_wave() {
+            if let Some(boss) = bosses.choose(rng) {
+                picks.push((boss, level));
+            }
+        }
+        let count = if picks.is_empty() { (1 + self.wave as usize).min(4) } else { 1 };
+        for _ in 0..count {
+            if let Some(archetype) = regular.choose(rng) {
+                picks.push((archetype, level.saturating_sub(2).max(1)));
+            }
+        }
+
+        let mut enemies: Vec<Enemy> = Vec::new();
+        for (archetype, level) in picks {
+            let mut enemy = Enemy::from_archetype(ids, catalog, archetype, level);
+            // keep names unique so they can be targeted by name
+            let same = enemies
+                .iter()
+                .filter(|e| e.info().name.starts_with(archetype.name.as_str()))
+                .count();
+            if same > 0 {
+                enemy.rename(format!("{} {}", archetype.name, same + 1));
+            }
+            enemies.push(enemy);
+        }
+        enemies
+    }
+
+    // settles a finished battle and puts the next wave in the world, returns what a won one dropped.
+    // the game and offline progress both go through here
+    pub fn next_wave(
+        &mut self,
+        outcome: Outcome,
+        world: &SharedWorld,
+        ids: &mut IdAllocator,
+        catalog: &Catalog,
+    ) -> Loot {
+        let heroes = world.borrow().heroes_dyn();
+        let loot = match outcome {
+            Outcome::Victory => {
+                let loot = inventory::roll(&world.borrow().enemies(), catalog, &mut *world.borrow().rng());
+                self.advance();
+                rest(&heroes);
+                loot
+            }
+            Outcome::Defeat => {
+                self.retreat();
+                regroup(&heroes);
+                Loot::default()
+            }
+        };
+        let enemies = self.spawn(ids, catalog, &mut *world.borrow().rng());
+        world.borrow_mut().set_enemies(enemies);
+        loot
+    }
+}
+
+// survivors catch their breath before the next wave
+pub fn rest(heroes: &[SharedEntity]) {
+    for hero in heroes {
+        let mut hero = hero.borrow_mut();
+        let amount = (hero.stat().max_hp as f32 * REST).round() as u32;
+        hero.heal(amount);
+    }
+}
+
+// back at the checkpoint everyone is patched up and ready
+pub fn regroup(heroes: &[SharedEntity]) {
+    for hero in heroes {
+        let mut hero = hero.borrow_mut();
+        for slot in hero.skills_mut() {
+            slot.ready_in = 0;
+        }
+        let stat = hero.stat_mut();
+        stat.curr_hp = stat.max_hp;
+        stat.curr_mp = stat.max_mp;
+        hero.status_mut().revive();
+    }
+}
+
+#[cfg(test)]
+use std::{cell::RefCell, rc::Rc};
+
+#[cfg(test)]
+use super::entities::hero::Hero;
+
+#[test]
+fn test_waves_stages_and_checkpoints() {
+    let mut encounter = Encounter::default();
+    assert_eq!(encounter.to_string(), "Stage 1 - Wave 1/3");
+    for _ in 0..WAVES_PER_STAGE {
+        encounter.advance();
+    }
+    assert_eq!((encounter.stage, encounter.wave), (2, 1));
+    assert_eq!(encounter.enemy_level(), 3);
+
+    while !encounter.is_boss_wave() {
+        encounter.advance();
+    }
+    assert_eq!(encounter.to_string(), "Stage 5 - Wave 3/3 (boss)");
+    assert_eq!(encounter.checkpoint, 1);
+    encounter.advance();
+    assert_eq!(encounter.checkpoint, 6);
+    encounter.advance();
+    encounter.retreat();
+    assert_eq!((encounter.stage, encounter.wave), (6, 1));
+}
+
+#[test]
+fn test_spawn_scales_and_brings_bosses() {
+    let catalog = Catalog::bundled();
+    let mut ids = IdAllocator::default();
+    let mut rng = super::world::test_rng();
+    let encounter = Encounter {
+        stage: 2,
+        wave: 2,
+        checkpoint: 1,
+    };
+    let enemies = encounter.spawn(&mut ids, &catalog, &mut rng);
+    assert_eq!(enemies.len(), 3);
+    assert!(enemies.iter().all(|e| e.progress().unwrap().level == 2));
+    let mut names: Vec<&str> = enemies.iter().map(|e| &*e.info().name).collect();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), 3);
+
+    let boss_wave = Encounter {
+        stage: BOSS_EVERY,
+        wave: WAVES_PER_STAGE,
+        checkpoint: 1,
+    };
+    let enemies = boss_wave.spawn(&mut ids, &catalog, &mut rng);
+    assert_eq!(enemies[0].info().name.as_ref(), "Ogre Warlord");
+    assert_eq!(enemies[0].progress().unwrap().level, boss_wave.enemy_level());
+    assert!(enemies[0].stat().max_hp > catalog.archetype("ogre_warlord").unwrap().stats.max_hp);
+}
+
+#[test]
+fn test_outcome_and_recovery() {
+    let heroes: Vec<SharedEntity> = vec![Rc::new(RefCell::new(Hero::new())), Rc::new(RefCell::new(Hero::new()))];
+    let enemies: Vec<SharedEntity> = vec![Rc::new(RefCell::new(Enemy::new()))];
+    assert_eq!(outcome(&heroes, &enemies), None);
+    enemies[0].borrow_mut().stat_and_status_mut().1.set_alive(false);
+    assert_eq!(outcome(&heroes, &enemies), Some(Outcome::Victory));
+
+    heroes[0].borrow_mut().stat_and_status_mut().0.curr_hp = 1;
+    heroes[1].borrow_mut().stat_and_status_mut().1.set_alive(false);
+    rest(&heroes);
+    assert_eq!(heroes[0].borrow().stat().curr_hp, 4);
+    for hero in &heroes {
+        hero.borrow_mut().stat_and_status_mut().1.set_alive(false);
+    }
+    assert_eq!(outcome(&heroes, &enemies), Some(Outcome::Defeat));
+    regroup(&heroes);
+    assert!(heroes.iter().all(|h| h.borrow().is_alive() && h.borrow().stat().curr_hp == 15));
+}
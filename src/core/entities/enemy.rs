@@ -1,10 +1,14 @@
-use rand::random;
 use serde::{Deserialize, Serialize};
 
-use crate::core::types::{Info, BasicStat};
+use crate::core::{
+    combat::{self, Action},
+    data::{Catalog, EnemyArchetype},
+    types::{Info, BasicStat, Element, Progress, SkillSlot, Status},
+    world::World,
+};
 
 use super::{
-    Entity, EntityType, Id,
+    Entity, EntityType, Id, IdAllocator, Strategy,
 };
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -12,19 +16,82 @@ pub struct Enemy {
     id: Id,
     info: Info,
     stat: BasicStat,
+    status: Status,
+    #[serde(default)]
+    skills: Vec<SkillSlot>,
+    #[serde(default)]
+    strategy: Strategy,
+    #[serde(default)]
+    progress: Progress,
+    // where the loot table comes from
+    #[serde(default)]
+    archetype: Box<str>,
+    #[serde(default)]
+    boss: bool,
+    #[serde(default)]
+    element: Option<Element>,
 }
 
 impl Enemy {
-    pub fn new() -> Self {
-        let id = random::<u8>();
+    // grown from the archetype's base stats the same way heroes level up
+    pub fn from_archetype(
+        ids: &mut IdAllocator,
+        catalog: &Catalog,
+        archetype: &EnemyArchetype,
+        level: u32,
+    ) -> Self {
+        let mut stat = archetype.stats.clone();
+        for level in 2..=level {
+            archetype.growth.level_up(&mut stat, level);
+        }
         Self {
-            id: id as u32,
+            id: ids.allocate(),
             info: Info {
-                name: format!("{}. Enemy", id).into(),
-                ..Default::default()
+                name: archetype.name.as_str().into(),
+                description: archetype.description.as_str().into(),
+                image_path: archetype.image_path.as_str().into(),
             },
             stat: BasicStat {
-                spd: id as i32,
+                curr_hp: stat.max_hp,
+                curr_mp: stat.max_mp,
+                ..stat
+            },
+            skills: catalog.skills(&archetype.skills),
+            strategy: archetype.strategy,
+            progress: Progress {
+                level: level.max(1),
+                xp: 0,
+            },
+            archetype: archetype.id.as_str().into(),
+            boss: archetype.boss,
+            element: archetype.element,
+            ..Default::default()
+        }
+    }
+
+    pub fn archetype(&self) -> &str {
+        &self.archetype
+    }
+
+    pub fn level(&self) -> u32 {
+        self.progress.level
+    }
+
+    pub fn set_id(&mut self, id: Id) {
+        self.id = id;
+    }
+
+    pub fn rename(&mut self, name: String) {
+        self.info.name = name.into();
+    }
+
+    #[cfg(test)]
+    pub fn new() -> Self {
+        let id = super::test_id();
+        Self {
+            id,
+            info: Info {
+                name: format!("{}. Enemy", id).into(),
                 ..Default::default()
             },
             ..Default::default()
@@ -45,7 +112,49 @@ impl Entity for Enemy {
         &self.stat
     }
 
+    fn status(&self) -> &Status {
+        &self.status
+    }
+
+    fn stat_and_status_mut(&mut self) -> (&mut BasicStat, &mut Status) {
+        (&mut self.stat, &mut self.status)
+    }
+
+    fn skills(&self) -> &[SkillSlot] {
+        &self.skills
+    }
+
+    fn skills_mut(&mut self) -> &mut [SkillSlot] {
+        &mut self.skills
+    }
+
     fn get_type(&self) -> EntityType {
-        EntityType::Enemy
+        if self.boss {
+            EntityType::Boss
+        } else {
+            EntityType::Enemy
+        }
+    }
+
+    fn act(&self, world: &World) -> Action {
+        combat::act(self, world)
+    }
+
+    fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    fn attack_element(&self) -> Option<Element> {
+        self.element
+    }
+
+    fn progress(&self) -> Option<&Progress> {
+        Some(&self.progress)
+    }
+
+    // tougher enemies are worth more
+    fn xp_reward(&self) -> u64 {
+        let s = &self.stat;
+        ((s.max_hp as i64 + (s.p_att + s.m_att + s.p_def + s.m_def) as i64) / 5).max(1) as u64
     }
 }
@@ -1,10 +1,14 @@
-use rand::random;
 use serde::{Deserialize, Serialize};
 
-use crate::core::types::{Info, BasicStat, Status};
+use crate::core::{
+    combat::{self, Action},
+    data::{Catalog, HeroClass},
+    types::{Info, BasicStat, Equipment, SkillSlot, Growth, Progress, Status},
+    world::World,
+};
 
 use super::{
-    Entity, EntityType, Id,
+    Entity, EntityType, Id, IdAllocator,
 };
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -13,19 +17,46 @@ pub struct Hero {
     pub info: Info,
     pub stat: BasicStat,
     pub status: Status,
+    #[serde(default)]
+    pub class: Box<str>,
+    #[serde(default)]
+    pub growth: Growth,
+    #[serde(default)]
+    pub progress: Progress,
+    #[serde(default)]
+    pub skills: Vec<SkillSlot>,
+    #[serde(default)]
+    pub equipment: Equipment,
 }
 
 impl Hero {
-    pub fn new() -> Self {
-        let id = random::<u8>();
+    pub fn from_class(ids: &mut IdAllocator, catalog: &Catalog, class: &HeroClass) -> Self {
         Self {
-            id: id as u32,
+            id: ids.allocate(),
             info: Info {
-                name: format!("{}. Hero", id).into(),
-                ..Default::default()
+                name: class.name.as_str().into(),
+                description: class.description.as_str().into(),
+                image_path: class.image_path.as_str().into(),
             },
             stat: BasicStat {
-                spd: id as i32,
+                curr_hp: class.stats.max_hp,
+                curr_mp: class.stats.max_mp,
+                ..class.stats.clone()
+            },
+            skills: catalog.skills(&class.skills),
+            class: class.id.as_str().into(),
+            growth: class.growth.clone(),
+            ..Default::default()
+        }
+    }
+
+    #[cfg(test)]
+    pub fn new() -> Self {
+        let id = super::test_id();
+        Self {
+            id,
+            info: Info {
+                name: format!("{}. Hero", id).into(),
                 ..Default::default()
             },
             ..Default::default()
@@ -46,7 +77,65 @@ impl Entity for Hero {
         &self.stat
     }
 
+    fn status(&self) -> &Status {
+        &self.status
+    }
+
+    fn stat_and_status_mut(&mut self) -> (&mut BasicStat, &mut Status) {
+        (&mut self.stat, &mut self.status)
+    }
+
+    fn skills(&self) -> &[SkillSlot] {
+        &self.skills
+    }
+
+    fn skills_mut(&mut self) -> &mut [SkillSlot] {
+        &mut self.skills
+    }
+
     fn get_type(&self) -> EntityType {
         EntityType::Hero
     }
+
+    fn act(&self, world: &World) -> Action {
+        combat::act(self, world)
+    }
+
+    fn progress(&self) -> Option<&Progress> {
+        Some(&self.progress)
+    }
+
+    fn equipment(&self) -> Option<&Equipment> {
+        Some(&self.equipment)
+    }
+
+    fn gain_xp(&mut self, xp: u64) -> Vec<String> {
+        let gained = self.progress.add_xp(xp);
+        (self.progress.level - gained + 1..=self.progress.level)
+            .map(|level| {
+                let gains = self
+                    .growth
+                    .level_up(&mut self.stat, level)
+                    .into_iter()
+                    .map(|(name, gain)| format!("{:+} {}", gain, name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!("{} reached level {} ({})", self.info.name, level, gains)
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_gain_xp_announces_each_level() {
+    let mut ids = IdAllocator::default();
+    let catalog = Catalog::bundled();
+    let mut hero = Hero::from_class(&mut ids, &catalog, catalog.class("warrior").unwrap());
+    let max_hp = hero.stat.max_hp;
+    let lines = hero.gain_xp(20 + 57);
+    assert_eq!(hero.progress.level, 3);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with(&format!("{} reached level 3 (+", hero.info.name)));
+    assert!(hero.stat.max_hp > max_hp);
+    assert_eq!(hero.stat.curr_hp, hero.stat.max_hp);
 }
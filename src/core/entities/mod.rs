@@ -1,20 +1,182 @@
-use std::fmt::Debug;
+use std::{cell::RefCell, fmt::Debug, rc::Rc};
 
-use super::types::{Info, BasicStat};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    combat::Action,
+    types::{status::Effect, Info, BasicStat, Element, Equipment, Progress, SkillSlot, Status},
+    world::World,
+};
 
 pub mod enemy;
 pub mod hero;
+pub mod policy;
+pub use policy::Strategy;
 
 pub type Id = u32;
 
+// hands out ids that are never reused, saved with the game so they stay unique after a load
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdAllocator {
+    next: Id,
+}
+
+impl Default for IdAllocator {
+    fn default() -> Self {
+        Self { next: 1 }
+    }
+}
+
+impl IdAllocator {
+    pub fn allocate(&mut self) -> Id {
+        let id = self.next;
+        self.next += 1;
+        id
+    }
+
+    // makes sure an id that is already in use is never handed out again
+    pub fn reserve(&mut self, id: Id) {
+        self.next = self.next.max(id + 1);
+    }
+}
+
+#[cfg(test)]
+pub fn test_id() -> Id {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    static NEXT: AtomicU32 = AtomicU32::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+pub type SharedEntity = Rc<RefCell<dyn Entity>>;
+
 pub trait Entity: Debug {
     fn id(&self) -> Id;
     fn info(&self) -> &Info;
     fn stat(&self) -> &BasicStat;
+    fn status(&self) -> &Status;
+    fn stat_and_status_mut(&mut self) -> (&mut BasicStat, &mut Status);
+    fn skills(&self) -> &[SkillSlot];
+    fn skills_mut(&mut self) -> &mut [SkillSlot];
     fn get_type(&self) -> EntityType;
+
+    fn stat_mut(&mut self) -> &mut BasicStat {
+        self.stat_and_status_mut().0
+    }
+
+    fn status_mut(&mut self) -> &mut Status {
+        self.stat_and_status_mut().1
+    }
+
+    fn is_alive(&self) -> bool {
+        self.status().is_alive()
+    }
+
+    // shields soak it up first, returns what actually got through
+    fn take_damage(&mut self, amount: u32) -> u32 {
+        if !self.is_alive() {
+            return 0;
+        }
+        let (stat, status) = self.stat_and_status_mut();
+        let damage = status.absorb(amount);
+        stat.curr_hp = stat.curr_hp.saturating_sub(damage);
+        status.check_alive(stat);
+        damage
+    }
+
+    // the dead need a revive, not a heal, returns what was restored
+    fn heal(&mut self, amount: u32) -> u32 {
+        if !self.is_alive() {
+            return 0;
+        }
+        let stat = self.stat_mut();
+        let amount = amount.min(stat.max_hp.saturating_sub(stat.curr_hp));
+        stat.curr_hp += amount;
+        amount
+    }
+
+    // the effect's own messages, without the name in front
+    fn apply_effect(&mut self, effect: Box<dyn Effect>) -> Vec<String> {
+        let (stat, status) = self.stat_and_status_mut();
+        status.apply_effect(effect, stat)
+    }
+
+    // what the entity does on its turn, looking at everyone in the world, see combat::act
+    fn act(&self, world: &World) -> Action;
+
+    // base, then what is worn, then whatever effects are active
+    fn effective_stat(&self) -> BasicStat {
+        match self.equipment() {
+            Some(equipment) => self.status().modified(&equipment.modified(self.stat())),
+            None => self.status().modified(self.stat()),
+        }
+    }
+
+    fn equipment(&self) -> Option<&Equipment> {
+        None
+    }
+
+    // basic attacks deal the weapon's element, plain damage without one
+    fn attack_element(&self) -> Option<Element> {
+        self.equipment()?.weapon.as_ref()?.element
+    }
+
+    // heroes play on auto unless told otherwise
+    fn strategy(&self) -> Strategy {
+        Strategy::Auto
+    }
+
+    fn progress(&self) -> Option<&Progress> {
+        None
+    }
+
+    fn xp_reward(&self) -> u64 {
+        0
+    }
+
+    // returns the level up announcements
+    fn gain_xp(&mut self, _xp: u64) -> Vec<String> {
+        Vec::new()
+    }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum EntityType {
     Hero,
     Enemy,
+    Boss,
+}
+
+impl EntityType {
+    pub fn is_party(&self) -> bool {
+        matches!(self, EntityType::Hero)
+    }
+}
+
+#[test]
+fn test_id_allocator() {
+    let mut ids = IdAllocator::default();
+    assert_eq!(ids.allocate(), 1);
+    assert_eq!(ids.allocate(), 2);
+    ids.reserve(10);
+    ids.reserve(4);
+    assert_eq!(ids.allocate(), 11);
+}
+
+#[test]
+fn test_damage_heal_and_sides() {
+    use super::types::effects::Shield;
+    use hero::Hero;
+
+    let mut hero = Hero::new();
+    hero.apply_effect(Box::new(Shield { amount: 4, duration: 1 }));
+    assert_eq!(hero.take_damage(6), 2);
+    assert_eq!(hero.stat().curr_hp, 13);
+    assert_eq!(hero.heal(10), 2);
+    assert_eq!(hero.take_damage(40), 40);
+    assert!(!hero.is_alive());
+    assert_eq!(hero.heal(5), 0);
+    assert_eq!(hero.take_damage(5), 0);
+
+    assert!(EntityType::Hero.is_party());
+    assert!(!EntityType::Boss.is_party());
 }
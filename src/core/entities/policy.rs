@@ -0,0 +1,332 @@
+use rand::{seq::SliceRandom, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    combat::{attack, Action},
+    types::{Skill, Target},
+};
+
+use super::Entity;
+
+// decides what an entity does on its turn, the opponents are never empty but may all be dead.
+// any randomness comes from the world's rng so runs can be replayed
+pub trait Policy {
+    fn choose(
+        &self,
+        actor: &dyn Entity,
+        allies: &[&dyn Entity],
+        opponents: &[&dyn Entity],
+        rng: &mut dyn RngCore,
+    ) -> Action;
+}
+
+// the policies an archetype can name in the data files
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Strategy {
+    Random,
+    LowestHp,
+    HighestThreat,
+    HealWhenLow,
+    StrongestSkill,
+    #[default]
+    Auto,
+}
+
+impl Strategy {
+    pub fn policy(&self) -> &'static dyn Policy {
+        match self {
+            Strategy::Random => &Random,
+            Strategy::LowestHp => &LowestHp,
+            Strategy::HighestThreat => &HighestThreat,
+            Strategy::HealWhenLow => &HealWhenLow,
+            Strategy::StrongestSkill => &StrongestSkill,
+            Strategy::Auto => &Auto,
+        }
+    }
+}
+
+pub fn hp_ratio(entity: &dyn Entity) -> f32 {
+    entity.stat().curr_hp as f32 / entity.stat().max_hp.max(1) as f32
+}
+
+fn living<'a>(entities: &[&'a dyn Entity]) -> Vec<&'a dyn Entity> {
+    entities.iter().copied().filter(|e| e.is_alive()).collect()
+}
+
+fn lowest_hp<'a>(entities: &[&'a dyn Entity]) -> Option<&'a dyn Entity> {
+    living(entities).into_iter().min_by_key(|e| e.stat().curr_hp)
+}
+
+// whoever hits the hardest
+fn threat(entity: &dyn Entity) -> i32 {
+    let stat = entity.effective_stat();
+    stat.p_att.max(stat.m_att)
+}
+
+// the strongest usable skill matching the filter
+fn ready_skill(actor: &dyn Entity, filter: impl Fn(&dyn Skill) -> bool) -> Option<usize> {
+    actor
+        .skills()
+        .iter()
+        .enumerate()
+        .filter(|(_, slot)| slot.usable_by(actor.stat()) && filter(&*slot.skill))
+        .max_by(|(_, a), (_, b)| a.skill.power().total_cmp(&b.skill.power()))
+        .map(|(idx, _)| idx)
+}
+
+// the strongest offensive skill on the target, or a plain attack
+fn offense(actor: &dyn Entity, target: &dyn Entity) -> Action {
+    match ready_skill(actor, |s| s.target().is_offensive()) {
+        Some(skill) => Action::Skill { skill, target: target.id() },
+        None => attack(actor, target),
+    }
+}
+
+// brings back the fallen first, then heals the most hurt ally below half hp,
+// or shields itself when it is the one in trouble
+fn support(actor: &dyn Entity, allies: &[&dyn Entity]) -> Option<Action> {
+    let fallen = allies.iter().find(|e| !e.is_alive());
+    if let (Some(fallen), Some(skill)) = (fallen, ready_skill(actor, |s| s.revives())) {
+        return Some(Action::Skill { skill, target: fallen.id() });
+    }
+    let hurt = living(allies)
+        .into_iter()
+        .chain((allies.is_empty() && actor.is_alive()).then_some(actor))
+        .filter(|e| hp_ratio(*e) < 0.5)
+        .min_by(|a, b| hp_ratio(*a).total_cmp(&hp_ratio(*b)));
+    if let Some(hurt) = hurt {
+        let heal = ready_skill(actor, |s| {
+            s.heals() && (s.target() != Target::Myself || hurt.id() == actor.id())
+        });
+        if let Some(skill) = heal {
+            return Some(Action::Skill { skill, target: hurt.id() });
+        }
+    }
+    if hp_ratio(actor) < 0.5 {
+        if let Some(skill) = ready_skill(actor, |s| s.target() == Target::Myself && !s.heals()) {
+            return Some(Action::Skill { skill, target: actor.id() });
+        }
+    }
+    None
+}
+
+pub struct Random;
+
+impl Policy for Random {
+    fn choose(
+        &self,
+        actor: &dyn Entity,
+        _allies: &[&dyn Entity],
+        opponents: &[&dyn Entity],
+        rng: &mut dyn RngCore,
+    ) -> Action {
+        let Some(target) = living(opponents).choose(rng).copied() else {
+            return Action::Wait;
+        };
+        let mut skills: Vec<usize> = actor
+            .skills()
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.usable_by(actor.stat()) && slot.skill.target().is_offensive())
+            .map(|(idx, _)| idx)
+            .collect();
+        // plain attacks are just as likely as any skill
+        skills.push(usize::MAX);
+        match skills.choose(rng) {
+            Some(&skill) if skill != usize::MAX => Action::Skill { skill, target: target.id() },
+            _ => attack(actor, target),
+        }
+    }
+}
+
+pub struct LowestHp;
+
+impl Policy for LowestHp {
+    fn choose(
+        &self,
+        actor: &dyn Entity,
+        _allies: &[&dyn Entity],
+        opponents: &[&dyn Entity],
+        _rng: &mut dyn RngCore,
+    ) -> Action {
+        match lowest_hp(opponents) {
+            Some(target) => offense(actor, target),
+            None => Action::Wait,
+        }
+    }
+}
+
+pub struct HighestThreat;
+
+impl Policy for HighestThreat {
+    fn choose(
+        &self,
+        actor: &dyn Entity,
+        _allies: &[&dyn Entity],
+        opponents: &[&dyn Entity],
+        _rng: &mut dyn RngCore,
+    ) -> Action {
+        match living(opponents).into_iter().max_by_key(|e| threat(*e)) {
+            Some(target) => offense(actor, target),
+            None => Action::Wait,
+        }
+    }
+}
+
+pub struct HealWhenLow;
+
+impl Policy for HealWhenLow {
+    fn choose(
+        &self,
+        actor: &dyn Entity,
+        allies: &[&dyn Entity],
+        opponents: &[&dyn Entity],
+        rng: &mut dyn RngCore,
+    ) -> Action {
+        if lowest_hp(opponents).is_none() {
+            return Action::Wait;
+        }
+        support(actor, allies).unwrap_or_else(|| LowestHp.choose(actor, allies, opponents, rng))
+    }
+}
+
+pub struct StrongestSkill;
+
+impl Policy for StrongestSkill {
+    fn choose(
+        &self,
+        actor: &dyn Entity,
+        allies: &[&dyn Entity],
+        opponents: &[&dyn Entity],
+        _rng: &mut dyn RngCore,
+    ) -> Action {
+        let Some(target) = lowest_hp(opponents) else {
+            return Action::Wait;
+        };
+        let Some(skill) = ready_skill(actor, |s| !s.revives()) else {
+            return attack(actor, target);
+        };
+        let target = match actor.skills()[skill].skill.target() {
+            Target::Enemy | Target::AllEnemies => target.id(),
+            Target::Myself => actor.id(),
+            Target::Ally | Target::AllAllies => living(allies)
+                .into_iter()
+                .min_by(|a, b| hp_ratio(*a).total_cmp(&hp_ratio(*b)))
+                .map_or(actor.id(), |e| e.id()),
+        };
+        Action::Skill { skill, target }
+    }
+}
+
+// what heroes do while idling: keep everyone standing, then hit as hard as possible
+pub struct Auto;
+
+impl Policy for Auto {
+    fn choose(
+        &self,
+        actor: &dyn Entity,
+        allies: &[&dyn Entity],
+        opponents: &[&dyn Entity],
+        _rng: &mut dyn RngCore,
+    ) -> Action {
+        let Some(target) = lowest_hp(opponents) else {
+            return Action::Wait;
+        };
+        support(actor, allies).unwrap_or_else(|| offense(actor, target))
+    }
+}
+
+#[cfg(test)]
+use super::{enemy::Enemy, hero::Hero};
+
+#[cfg(test)]
+use crate::core::{
+    combat::DamageKind,
+    world::test_rng,
+    types::{
+        skills::{Mend, Revive},
+        SkillSlot,
+    },
+};
+
+#[test]
+fn test_targeting_strategies() {
+    let enemy = Enemy::new();
+    let mut weak = Hero::new();
+    weak.stat.curr_hp = 5;
+    let mut strong = Hero::new();
+    strong.stat.p_att = 40;
+    let mut dead = Hero::new();
+    dead.stat.p_att = 99;
+    dead.status.set_alive(false);
+    let heroes: [&dyn Entity; 3] = [&weak, &strong, &dead];
+
+    let mut rng = test_rng();
+    let mut target = |strategy: Strategy| match strategy.policy().choose(&enemy, &[], &heroes, &mut rng) {
+        Action::Attack { target, .. } | Action::Skill { target, .. } => Some(target),
+        Action::Wait => None,
+    };
+    assert_eq!(target(Strategy::LowestHp), Some(weak.id));
+    assert_eq!(target(Strategy::HighestThreat), Some(strong.id));
+    for _ in 0..20 {
+        assert_ne!(target(Strategy::Random), Some(dead.id));
+    }
+    assert_eq!(Strategy::Auto.policy().choose(&enemy, &[], &[&dead], &mut test_rng()), Action::Wait);
+    assert_eq!(
+        Strategy::Random.policy().choose(&enemy, &[], &[&weak], &mut test_rng()),
+        Action::Attack {
+            target: weak.id,
+            kind: DamageKind::Physical
+        }
+    );
+}
+
+#[test]
+fn test_heal_when_low() {
+    let mut healer = Hero::new();
+    healer.skills = vec![SkillSlot::new(Box::new(Mend {
+        name: "Heal".to_owned(),
+        power: 1.,
+        target: Target::Ally,
+        cost: 0,
+        cooldown: 0,
+        effect: None,
+    }))];
+    let enemy = Enemy::new();
+    let mut hurt = Hero::new();
+    hurt.stat.curr_hp = 7;
+    let policy = Strategy::HealWhenLow.policy();
+
+    let action = policy.choose(&healer, &[&healer, &hurt], &[&enemy], &mut test_rng());
+    assert_eq!(action, Action::Skill { skill: 0, target: hurt.id });
+    // nobody needs healing, the healer goes after the weakest opponent
+    hurt.stat.curr_hp = 15;
+    let action = policy.choose(&healer, &[&healer, &hurt], &[&enemy], &mut test_rng());
+    assert!(matches!(action, Action::Attack { target, .. } if target == enemy.id()));
+    // without allies it looks after itself
+    healer.stat.curr_hp = 2;
+    let action = policy.choose(&healer, &[], &[&enemy], &mut test_rng());
+    assert_eq!(action, Action::Skill { skill: 0, target: healer.id });
+}
+
+#[test]
+fn test_auto_revives_the_fallen_first() {
+    let mut cleric = Hero::new();
+    cleric.skills = vec![SkillSlot::new(Box::new(Revive {
+        name: "Revive".to_owned(),
+        power: 0.5,
+        target: Target::Ally,
+        cost: 10,
+        cooldown: 3,
+    }))];
+    let mut fallen = Hero::new();
+    fallen.status.set_alive(false);
+    let enemy = Enemy::new();
+
+    let action = Strategy::Auto.policy().choose(&cleric, &[&cleric, &fallen], &[&enemy], &mut test_rng());
+    assert_eq!(action, Action::Skill { skill: 0, target: fallen.id });
+    // without the mana it fights instead
+    cleric.stat.curr_mp = 5;
+    let action = Strategy::Auto.policy().choose(&cleric, &[&cleric, &fallen], &[&enemy], &mut test_rng());
+    assert!(matches!(action, Action::Attack { .. }));
+}
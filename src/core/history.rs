@@ -0,0 +1,133 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::save::data_dir;
+
+#[derive(Debug, Default)]
+pub struct History {
+    entries: Vec<String>,
+    max_len: usize,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            max_len,
+            path: None,
+        }
+    }
+
+    pub fn file() -> PathBuf {
+        data_dir().join("history")
+    }
+
+    // a missing or unreadable file just means an empty history
+    pub fn load(path: PathBuf, max_len: usize) -> Self {
+        let mut history = Self::new(max_len);
+        if let Ok(content) = fs::read_to_string(&path) {
+            for line in content.lines() {
+                history.insert(line);
+            }
+        }
+        history.path = Some(path);
+        history
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&str> {
+        self.entries.get(idx).map(|e| e.as_str())
+    }
+
+    fn insert(&mut self, entry: &str) -> bool {
+        let entry = entry.trim();
+        if entry.is_empty() || self.max_len == 0 {
+            return false;
+        }
+        // re-running a command moves it to the end instead of duplicating it
+        self.entries.retain(|e| e != entry);
+        self.entries.push(entry.to_owned());
+        let overflow = self.entries.len().saturating_sub(self.max_len);
+        self.entries.drain(..overflow);
+        true
+    }
+
+    pub fn push(&mut self, entry: &str) -> Result<()> {
+        if self.insert(entry) {
+            self.persist()?;
+        }
+        Ok(())
+    }
+
+    fn persist(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("cannot create {}", dir.display()))?;
+        }
+        let mut content = self.entries.join("\n");
+        content.push('\n');
+        fs::write(path, content).with_context(|| format!("cannot write {}", path.display()))
+    }
+
+    // newest entry older than `before` that contains the query
+    pub fn search(&self, query: &str, before: usize) -> Option<usize> {
+        self.entries[..before.min(self.entries.len())]
+            .iter()
+            .rposition(|e| e.contains(query))
+    }
+}
+
+#[cfg(test)]
+use crate::testing::TestDir;
+
+#[cfg(test)]
+fn test_history(entries: &[&str], max_len: usize) -> History {
+    let mut history = History::new(max_len);
+    for entry in entries {
+        history.push(entry).unwrap();
+    }
+    history
+}
+
+#[test]
+fn test_push_dedups_and_caps() {
+    let history = test_history(&["save", "load 1", " ", "save", "speed 2", "help"], 3);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(0), Some("save"));
+    assert_eq!(history.get(2), Some("help"));
+
+    let history = test_history(&["save"], 0);
+    assert_eq!(history.len(), 0);
+}
+
+#[test]
+fn test_search() {
+    let history = test_history(&["save a", "load a", "save b", "help"], 10);
+    assert_eq!(history.search("save", 4), Some(2));
+    assert_eq!(history.search("save", 2), Some(0));
+    assert_eq!(history.search("save", 0), None);
+    assert_eq!(history.search("quit", 4), None);
+    assert_eq!(history.search("", 10), Some(3));
+}
+
+#[test]
+fn test_history_persists() {
+    let dir = TestDir::new("history");
+    let path = dir.join("history");
+    let mut history = History::load(path.clone(), 2);
+    history.push("save").unwrap();
+    history.push("load").unwrap();
+    history.push("help").unwrap();
+
+    let loaded = History::load(path, 5);
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded.get(0), Some("load"));
+    assert_eq!(loaded.get(1), Some("help"));
+}
@@ -0,0 +1,286 @@
+use std::{cell::RefCell, fmt::Display, rc::Rc};
+
+use anyhow::{anyhow, bail, Result};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    data::{Catalog, ItemDefinition, GOLD},
+    entities::{enemy::Enemy, hero::Hero},
+    types::{Gear, Slot},
+};
+
+const CAPACITY: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stack {
+    pub item: String,
+    pub count: u32,
+}
+
+// shared by the whole party
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Inventory {
+    pub gold: u64,
+    // in stacks, not items
+    pub capacity: usize,
+    stacks: Vec<Stack>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            gold: 0,
+            capacity: CAPACITY,
+            stacks: Vec::new(),
+        }
+    }
+}
+
+impl Inventory {
+    pub fn stacks(&self) -> &[Stack] {
+        &self.stacks
+    }
+
+    // tops up existing stacks first, then opens new ones while there is room, returns what didn't fit
+    pub fn add(&mut self, item: &ItemDefinition, mut count: u32) -> u32 {
+        for stack in self.stacks.iter_mut().filter(|s| s.item == item.id) {
+            let moved = count.min(item.max_stack.saturating_sub(stack.count));
+            stack.count += moved;
+            count -= moved;
+        }
+        while count > 0 && self.stacks.len() < self.capacity {
+            let moved = count.min(item.max_stack);
+            self.stacks.push(Stack {
+                item: item.id.clone(),
+                count: moved,
+            });
+            count -= moved;
+        }
+        count
+    }
+
+    // takes a single item out, from the last stack holding it
+    fn take(&mut self, item: &str) -> bool {
+        let Some(idx) = self.stacks.iter().rposition(|s| s.item == item) else {
+            return false;
+        };
+        self.stacks[idx].count -= 1;
+        if self.stacks[idx].count == 0 {
+            self.stacks.remove(idx);
+        }
+        true
+    }
+
+    // gear never stacks, so what comes off always fits where the new piece was
+    fn put_back(&mut self, gear: Gear) {
+        self.stacks.push(Stack {
+            item: gear.item,
+            count: 1,
+        });
+    }
+
+    pub fn equip(&mut self, hero: &mut Hero, item: &ItemDefinition) -> Result<String> {
+        let gear = item
+            .gear()
+            .ok_or_else(|| anyhow!("{} can't be worn", item.name))?;
+        if !self.take(&item.id) {
+            bail!("no {} in the inventory", item.name);
+        }
+        let slot = gear.slot;
+        let line = match hero.equipment.equip(gear) {
+            Some(old) => {
+                let line = format!("{} swaps {} for {}", hero.info.name, old.name, item.name);
+                self.put_back(old);
+                line
+            }
+            None => format!("{} equips {} as {}", hero.info.name, item.name, slot),
+        };
+        Ok(line)
+    }
+
+    pub fn unequip(&mut self, hero: &mut Hero, slot: Slot) -> Result<String> {
+        if hero.equipment.get(slot).is_none() {
+            bail!("{} has no {}", hero.info.name, slot);
+        }
+        if self.stacks.len() >= self.capacity {
+            bail!("inventory is full");
+        }
+        let gear = hero.equipment.unequip(slot).expect("checked above");
+        let line = format!("{} takes off {}", hero.info.name, gear.name);
+        self.put_back(gear);
+        Ok(line)
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Loot {
+    pub gold: u64,
+    pub items: Vec<(String, u32)>,
+}
+
+impl Loot {
+    fn push(&mut self, item: &str) {
+        match self.items.iter_mut().find(|(i, _)| i == item) {
+            Some((_, count)) => *count += 1,
+            None => self.items.push((item.to_owned(), 1)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gold == 0 && self.items.is_empty()
+    }
+
+    // into the inventory, returns the log lines
+    pub fn collect(self, inventory: &mut Inventory, catalog: &Catalog) -> Vec<String> {
+        if self.is_empty() {
+            return Vec::new();
+        }
+        inventory.gold += self.gold;
+        let mut found = Vec::new();
+        let mut lost = Vec::new();
+        if self.gold > 0 {
+            found.push(format!("{} gold", self.gold));
+        }
+        for (item, count) in self.items {
+            let Ok(definition) = catalog.item(&item) else {
+                continue;
+            };
+            found.push(ItemCount(&definition.name, count).to_string());
+            let left = inventory.add(definition, count);
+            if left > 0 {
+                lost.push(ItemCount(&definition.name, left).to_string());
+            }
+        }
+        let mut lines = vec![format!("Loot: {}", found.join(", "))];
+        if !lost.is_empty() {
+            lines.push(format!("Inventory full, left behind {}", lost.join(", ")));
+        }
+        lines
+    }
+}
+
+struct ItemCount<'a>(&'a str, u32);
+
+impl Display for ItemCount<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.1 > 1 {
+            write!(f, "{} x{}", self.0, self.1)
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+// one roll on each defeated enemy's table, gold scales with their level
+pub fn roll(enemies: &[Rc<RefCell<Enemy>>], catalog: &Catalog, rng: &mut impl Rng) -> Loot {
+    let mut loot = Loot::default();
+    for enemy in enemies.iter().map(|e| e.borrow()) {
+        let Ok(archetype) = catalog.archetype(enemy.archetype()) else {
+            continue;
+        };
+        let Ok(entry) = archetype.loot.choose_weighted(rng, |l| l.weight) else {
+            continue;
+        };
+        if entry.item == GOLD {
+            loot.gold += enemy.level() as u64 * rng.gen_range(2..=5);
+        } else {
+            loot.push(&entry.item);
+        }
+    }
+    loot
+}
+
+#[cfg(test)]
+impl Inventory {
+    fn count(&self, item: &str) -> u32 {
+        self.stacks.iter().filter(|s| s.item == item).map(|s| s.count).sum()
+    }
+}
+
+#[cfg(test)]
+fn test_item(id: &str, max_stack: u32) -> ItemDefinition {
+    ItemDefinition {
+        id: id.to_owned(),
+        name: id.to_owned(),
+        description: String::new(),
+        max_stack,
+        value: 1,
+        slot: None,
+        modifiers: Vec::new(),
+        element: None,
+    }
+}
+
+#[test]
+fn test_stacking_and_capacity() {
+    let mut inventory = Inventory {
+        capacity: 3,
+        ..Default::default()
+    };
+    let gel = test_item("gel", 5);
+    let sword = test_item("sword", 1);
+    assert_eq!(inventory.add(&gel, 7), 0);
+    assert_eq!(inventory.stacks().len(), 2);
+    assert_eq!(inventory.add(&gel, 2), 0);
+    assert_eq!(inventory.stacks().len(), 2);
+    assert_eq!(inventory.count("gel"), 9);
+    assert_eq!(inventory.add(&sword, 2), 1);
+    assert_eq!(inventory.add(&gel, 3), 2);
+    assert_eq!(inventory.count("gel"), 10);
+
+    assert!(inventory.take("sword"));
+    assert!(!inventory.take("sword"));
+    assert_eq!(inventory.stacks().len(), 2);
+}
+
+#[test]
+fn test_equip_swaps_with_the_inventory() {
+    let catalog = Catalog::bundled();
+    let mut hero = Hero::new();
+    let mut inventory = Inventory::default();
+    let dagger = catalog.item("rusty_dagger").unwrap();
+    let club = catalog.item("warlord_club").unwrap();
+    assert!(inventory.equip(&mut hero, dagger).is_err());
+    assert!(inventory.equip(&mut hero, catalog.item("slime_gel").unwrap()).is_err());
+
+    inventory.add(dagger, 1);
+    inventory.add(club, 1);
+    inventory.equip(&mut hero, dagger).unwrap();
+    assert_eq!(inventory.stacks().len(), 1);
+    let line = inventory.equip(&mut hero, club).unwrap();
+    assert!(line.contains("swaps Rusty Dagger for Warlord's Club"));
+    assert_eq!(inventory.count("rusty_dagger"), 1);
+    assert_eq!(hero.equipment.weapon.as_ref().unwrap().item, "warlord_club");
+    // the club trades speed for a lot of attack
+    let stat = super::entities::Entity::effective_stat(&hero);
+    assert!(stat.p_att > hero.stat.p_att && stat.spd < hero.stat.spd);
+
+    inventory.capacity = 1;
+    assert!(inventory.unequip(&mut hero, Slot::Weapon).is_err());
+    inventory.capacity = 2;
+    inventory.unequip(&mut hero, Slot::Weapon).unwrap();
+    assert!(hero.equipment.weapon.is_none());
+    assert!(inventory.unequip(&mut hero, Slot::Weapon).is_err());
+}
+
+#[test]
+fn test_loot_rolls_and_collects() {
+    let catalog = Catalog::bundled();
+    let mut ids = super::entities::IdAllocator::default();
+    let slime = catalog.archetype("slime").unwrap();
+    let enemies: Vec<Rc<RefCell<Enemy>>> = (0..20)
+        .map(|_| Rc::new(RefCell::new(Enemy::from_archetype(&mut ids, &catalog, slime, 3))))
+        .collect();
+    let loot = roll(&enemies, &catalog, &mut super::world::test_rng());
+    let gel = loot.items.iter().find(|(i, _)| i == "slime_gel").map_or(0, |(_, c)| *c);
+    assert!(loot.gold > 0 && gel > 0);
+    assert!(loot.gold >= (20 - gel as u64) * 6);
+
+    let mut inventory = Inventory::default();
+    let lines = loot.collect(&mut inventory, &catalog);
+    assert!(lines[0].starts_with("Loot: "));
+    assert!(lines[0].contains("Slime Gel"));
+    assert_eq!(inventory.count("slime_gel"), gel);
+    assert!(inventory.gold > 0);
+}
@@ -12,6 +12,30 @@ use states::{InGame, State, StateType, Welcome};
 
 mod entities;
 
+mod data;
+pub use data::Catalog;
+
+mod combat;
+
+mod commands;
+
+mod save;
+pub use save::data_dir;
+
+mod history;
+
+mod offline;
+
+mod encounter;
+
+mod inventory;
+
+mod world;
+
+mod timeline;
+
+pub mod sim;
+
 mod components;
 
 mod consts;
@@ -33,20 +57,20 @@ impl App {
         }
     }
 
-    pub fn init(&mut self) -> Result<()> {
-        self.state.init();
+    pub fn init(&mut self, ctx: &Context) -> Result<()> {
+        self.state.init(ctx);
         Ok(())
     }
 
     pub fn handle_event(&mut self, ctx: &mut Context, event: Event) {
         if let Some(state) = self.state.handle_event(event, ctx) {
-            self.change_state(state);
+            self.change_state(state, ctx);
         }
     }
 
     pub fn update(&mut self, ctx: &mut Context) {
         if let Some(state) = self.state.update(ctx) {
-            self.change_state(state);
+            self.change_state(state, ctx);
         }
     }
 
@@ -66,14 +90,17 @@ impl App {
                     Block::new()
                         .borders(Borders::ALL)
                         .fg(PRIMARY)
-                        .title(" Debug "),
+                        .title(match self.state.seed() {
+                            Some(seed) => format!(" Debug - seed {} ", seed),
+                            None => " Debug ".to_owned(),
+                        }),
                 )
                 .wrap(Wrap { trim: false }),
             layout[1],
         );
     }
 
-    fn change_state(&mut self, state: StateType) {
+    fn change_state(&mut self, state: StateType, ctx: &Context) {
         let maybe_new_state: Option<Box<dyn State>> = match state {
             StateType::Welcome if self.state.get_type() != StateType::Welcome => {
                 Some(Box::new(Welcome::new()))
@@ -86,7 +113,7 @@ impl App {
         if let Some(new_state) = maybe_new_state {
             self.state.destroy();
             self.state = new_state;
-            self.state.init();
+            self.state.init(ctx);
         }
     }
 }
@@ -0,0 +1,194 @@
+use std::{fmt::Display, time::Duration};
+
+use super::{
+    combat,
+    consts::TURN_DELAY,
+    data::Catalog,
+    encounter::{self, Encounter, Outcome},
+    entities::{IdAllocator, SharedEntity},
+    inventory::Inventory,
+    timeline::Timeline,
+};
+
+pub const MAX_OFFLINE: Duration = Duration::from_secs(8 * 60 * 60);
+const BATCH: u64 = 64;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub elapsed: Duration,
+    pub capped: bool,
+    pub turns: u64,
+    pub battles_won: u32,
+    pub enemies_defeated: usize,
+    pub heroes_lost: usize,
+    pub levels_gained: u32,
+    pub gold: u64,
+    pub items: u32,
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secs = self.elapsed.as_secs();
+        write!(
+            f,
+            "While you were away ({}h {}m {}s{}): {} turns, {} battles won, {} enemies defeated, {} heroes lost, {} levels gained, {} gold and {} items found",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60,
+            if self.capped { ", capped" } else { "" },
+            self.turns,
+            self.battles_won,
+            self.enemies_defeated,
+            self.heroes_lost,
+            self.levels_gained,
+            self.gold,
+            self.items,
+        )
+    }
+}
+
+fn alive(side: &[SharedEntity]) -> usize {
+    side.iter().filter(|e| e.borrow().is_alive()).count()
+}
+
+fn levels(side: &[SharedEntity]) -> u32 {
+    side.iter()
+        .filter_map(|e| e.borrow().progress().map(|p| p.level))
+        .sum()
+}
+
+fn total_hp(side: &[SharedEntity]) -> u64 {
+    side.iter().map(|e| e.borrow().stat().curr_hp as u64).sum()
+}
+
+// plays the missed time the way InGame::update would, waves, loot and all, just without
+// waiting for the timer. a finished wave costs the pause the game would have sat through
+pub fn simulate(
+    elapsed: Duration,
+    pause: Duration,
+    timeline: &mut Timeline,
+    encounter: &mut Encounter,
+    ids: &mut IdAllocator,
+    inventory: &mut Inventory,
+    catalog: &Catalog,
+) -> Summary {
+    let world = timeline.world().clone();
+    let heroes = world.borrow().heroes_dyn();
+    let mut summary = Summary {
+        elapsed: elapsed.min(MAX_OFFLINE),
+        capped: elapsed > MAX_OFFLINE,
+        ..Default::default()
+    };
+    let levels_before = levels(&heroes);
+    let pause_turns = (pause.as_millis() / TURN_DELAY.as_millis()).max(1) as u64;
+    let mut remaining = (summary.elapsed.as_millis() / TURN_DELAY.as_millis()) as u64;
+
+    'batches: while remaining > 0 {
+        let hp_before = total_hp(&heroes) + total_hp(&world.borrow().enemies_dyn());
+        for _ in 0..remaining.min(BATCH) {
+            let enemies = world.borrow().enemies_dyn();
+            if let Some(outcome) = encounter::outcome(&heroes, &enemies) {
+                if outcome == Outcome::Victory {
+                    summary.battles_won += 1;
+                }
+                let loot = encounter.next_wave(outcome, &world, ids, catalog);
+                summary.gold += loot.gold;
+                summary.items += loot.items.iter().map(|(_, count)| count).sum::<u32>();
+                loot.collect(inventory, catalog);
+                timeline.sync();
+                remaining = remaining.saturating_sub(pause_turns);
+                continue 'batches;
+            }
+            let (heroes_alive, enemies_alive) = (alive(&heroes), alive(&enemies));
+            if let Some(id) = timeline.current() {
+                combat::take_turn(id, &world.borrow(), None);
+            }
+            timeline.pop();
+            summary.heroes_lost += heroes_alive.saturating_sub(alive(&heroes));
+            summary.enemies_defeated += enemies_alive.saturating_sub(alive(&enemies));
+            summary.turns += 1;
+            remaining -= 1;
+        }
+        // a whole batch without any hp change means nobody can hurt anybody anymore
+        if total_hp(&heroes) + total_hp(&world.borrow().enemies_dyn()) == hp_before {
+            break;
+        }
+    }
+
+    summary.levels_gained = levels(&heroes) - levels_before;
+    summary
+}
+
+#[cfg(test)]
+use super::{
+    entities::{enemy::Enemy, hero::Hero},
+    types::effects::Stun,
+    world::World,
+};
+
+#[cfg(test)]
+fn test_battle(p_att: i32) -> (Vec<SharedEntity>, Vec<SharedEntity>, Timeline) {
+    let mut hero = Hero::new();
+    hero.stat.p_att = p_att;
+    hero.stat.max_hp = 10000;
+    hero.stat.curr_hp = 10000;
+    let world = World::shared();
+    world.borrow_mut().add_hero(hero);
+    world.borrow_mut().add_enemy(Enemy::new());
+    let (heroes, enemies) = (world.borrow().heroes_dyn(), world.borrow().enemies_dyn());
+    let mut timeline = Timeline::new(world);
+    timeline.sync();
+    (heroes, enemies, timeline)
+}
+
+#[cfg(test)]
+fn test_simulate(elapsed: Duration, timeline: &mut Timeline) -> (Summary, Encounter, Inventory) {
+    let mut ids = IdAllocator::default();
+    ids.reserve(timeline.world().borrow().ids().into_iter().max().unwrap_or(0));
+    let (mut encounter, mut inventory) = (Encounter::default(), Inventory::default());
+    let summary = simulate(
+        elapsed,
+        Duration::from_secs(3),
+        timeline,
+        &mut encounter,
+        &mut ids,
+        &mut inventory,
+        &Catalog::bundled(),
+    );
+    (summary, encounter, inventory)
+}
+
+#[test]
+fn test_simulate_moves_on_through_waves_and_collects_loot() {
+    let (_, enemies, mut timeline) = test_battle(400);
+    let (summary, encounter, inventory) = test_simulate(Duration::from_secs(10 * 60), &mut timeline);
+    assert!(summary.battles_won > 1);
+    assert!(summary.enemies_defeated > summary.battles_won as usize);
+    assert_eq!(summary.heroes_lost, 0);
+    assert!(!enemies[0].borrow().is_alive());
+    assert_ne!(encounter, Encounter::default());
+    assert_eq!(inventory.gold, summary.gold);
+    assert!(summary.gold > 0 || summary.items > 0);
+}
+
+#[test]
+fn test_simulate_respects_elapsed_time_and_cap() {
+    let (_, _, mut timeline) = test_battle(40);
+    let (summary, _, _) = test_simulate(Duration::from_secs(3), &mut timeline);
+    assert_eq!(summary.turns, 1);
+
+    let (_, _, mut timeline) = test_battle(40);
+    let (summary, _, _) = test_simulate(MAX_OFFLINE * 2, &mut timeline);
+    assert!(summary.capped);
+    assert_eq!(summary.elapsed, MAX_OFFLINE);
+}
+
+#[test]
+fn test_simulate_stops_on_stalemate() {
+    let (heroes, enemies, mut timeline) = test_battle(0);
+    heroes[0].borrow_mut().stat_and_status_mut().0.m_att = 0;
+    enemies[0].borrow_mut().apply_effect(Box::new(Stun { duration: i32::MAX }));
+    let (summary, _, _) = test_simulate(MAX_OFFLINE, &mut timeline);
+    assert_eq!(summary.turns, BATCH);
+    assert_eq!(summary.battles_won, 0);
+}
@@ -0,0 +1,332 @@
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fs,
+    io::ErrorKind,
+    path::PathBuf,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    encounter::Encounter,
+    inventory::Inventory,
+    entities::{enemy::Enemy, hero::Hero, Entity, Id, IdAllocator},
+    timeline::Gauge,
+};
+
+// 2: ids come from the saved allocator instead of random bytes
+// 3: rounds were replaced by the action value timeline
+// 4: the party inventory, older saves start with an empty one
+// 5: hero equipment, older heroes start with nothing equipped
+// 6: the run's seed and rng position, older saves carry on with a fresh seed
+// 7: crits, accuracy, evasion and resistances, older stats get the defaults
+// 8: elements on weapons and enemy attacks, older ones hit without one
+pub const SAVE_VERSION: u32 = 8;
+pub const AUTOSAVE_SLOT: &str = "auto";
+
+// slots that exist but didn't load, with why
+pub type Unreadable = Vec<(String, anyhow::Error)>;
+// seeded runs autosave here instead, so a replay never overwrites the real game
+const SEEDED_PREFIX: &str = "seed-";
+
+pub fn seeded_slot(seed: u64) -> String {
+    format!("{}{}", SEEDED_PREFIX, seed)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub saved_at: u64,
+    pub heroes: Vec<Rc<RefCell<Hero>>>,
+    pub enemies: Vec<Rc<RefCell<Enemy>>>,
+    #[serde(default)]
+    pub timeline: Vec<Gauge>,
+    pub current_turn: Option<Id>,
+    pub log: Vec<String>,
+    pub timer_ms: u64,
+    #[serde(default)]
+    pub ids: IdAllocator,
+    #[serde(default)]
+    pub encounter: Encounter,
+    #[serde(default)]
+    pub inventory: Inventory,
+    // the run's seed and how far into it the save was made, enough to replay it exactly
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub rng_position: u128,
+}
+
+impl SaveGame {
+    pub fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    // older saves can hold colliding ids, later duplicates get fresh ones
+    fn fix_ids(&mut self) {
+        let mut seen = HashSet::new();
+        for hero in &self.heroes {
+            self.ids.reserve(hero.borrow().id);
+        }
+        for enemy in &self.enemies {
+            self.ids.reserve(enemy.borrow().id());
+        }
+        for hero in &self.heroes {
+            let mut hero = hero.borrow_mut();
+            if !seen.insert(hero.id) {
+                hero.id = self.ids.allocate();
+            }
+        }
+        for enemy in &self.enemies {
+            let mut enemy = enemy.borrow_mut();
+            if !seen.insert(enemy.id()) {
+                enemy.set_id(self.ids.allocate());
+            }
+        }
+    }
+}
+
+pub fn data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("idle-termquest")
+}
+
+pub struct Saves {
+    dir: PathBuf,
+}
+
+impl Saves {
+    pub fn new() -> Self {
+        Self::at(data_dir().join("saves"))
+    }
+
+    pub fn at(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, slot: &str) -> Result<PathBuf> {
+        if slot.is_empty()
+            || !slot
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!("invalid slot name '{}', use letters, digits, '-' or '_'", slot);
+        }
+        Ok(self.dir.join(format!("{}.json", slot)))
+    }
+
+    pub fn write(&self, slot: &str, save: &SaveGame) -> Result<PathBuf> {
+        let path = self.path(slot)?;
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("cannot create {}", self.dir.display()))?;
+        // write then rename so a crash mid-save doesn't eat the previous one
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(save)?)
+            .with_context(|| format!("cannot write {}", tmp.display()))?;
+        fs::rename(&tmp, &path).with_context(|| format!("cannot write {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn read(&self, slot: &str) -> Result<SaveGame> {
+        let path = self.path(slot)?;
+        let content = fs::read_to_string(&path).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => anyhow!("no save in slot '{}'", slot),
+            _ => anyhow::Error::new(e).context(format!("failed to read {}", path.display())),
+        })?;
+        let version = serde_json::from_str::<serde_json::Value>(&content)
+            .with_context(|| format!("slot '{}' is not valid json", slot))?
+            .get("version")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| anyhow!("slot '{}' has no save version", slot))?;
+        if version > SAVE_VERSION as u64 {
+            bail!(
+                "slot '{}' was saved by a newer version ({} > {})",
+                slot,
+                version,
+                SAVE_VERSION
+            );
+        }
+        let mut save: SaveGame = serde_json::from_str(&content)
+            .with_context(|| format!("slot '{}' is corrupted", slot))?;
+        save.fix_ids();
+        save.version = SAVE_VERSION;
+        Ok(save)
+    }
+
+    pub fn slots(&self) -> Vec<String> {
+        let mut slots: Vec<String> = fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                    .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        slots.sort();
+        slots
+    }
+
+    // the newest save that loads, plus the slots that didn't so nothing overwrites them blindly
+    pub fn latest(&self) -> (Option<(String, SaveGame)>, Unreadable) {
+        let mut latest: Option<(String, SaveGame)> = None;
+        let mut failed = Vec::new();
+        for slot in self.slots().into_iter().filter(|slot| !slot.starts_with(SEEDED_PREFIX)) {
+            match self.read(&slot) {
+                Ok(save) => {
+                    let newer = match &latest {
+                        Some((_, best)) => save.saved_at >= best.saved_at,
+                        None => true,
+                    };
+                    if newer {
+                        latest = Some((slot, save));
+                    }
+                }
+                Err(e) => failed.push((slot, e)),
+            }
+        }
+        (latest, failed)
+    }
+}
+
+#[cfg(test)]
+use crate::testing::TestDir;
+
+// the dir has to outlive the saves in it
+#[cfg(test)]
+fn test_saves(name: &str) -> (TestDir, Saves) {
+    let dir = TestDir::new(&format!("saves-{}", name));
+    let saves = Saves::at(dir.to_path_buf());
+    (dir, saves)
+}
+
+#[cfg(test)]
+fn test_save(saved_at: u64) -> SaveGame {
+    let hero = Hero::new();
+    let id = hero.id;
+    SaveGame {
+        version: SAVE_VERSION,
+        saved_at,
+        heroes: vec![Rc::new(RefCell::new(hero))],
+        enemies: vec![Rc::new(RefCell::new(Enemy::new()))],
+        timeline: vec![Gauge { id, distance: 0. }],
+        current_turn: Some(id),
+        log: vec!["hello".to_owned()],
+        timer_ms: 500,
+        ids: IdAllocator::default(),
+        encounter: Encounter::default(),
+        inventory: Inventory::default(),
+        seed: Some(7),
+        rng_position: u64::MAX as u128 + 3,
+    }
+}
+
+#[test]
+fn test_save_roundtrip() {
+    let (_dir, saves) = test_saves("roundtrip");
+    let mut save = test_save(10);
+    save.inventory.gold = 42;
+    saves.write("1", &save).unwrap();
+    let loaded = saves.read("1").unwrap();
+    assert_eq!(loaded.heroes[0].borrow().id, save.heroes[0].borrow().id);
+    assert_eq!(loaded.timeline, save.timeline);
+    assert_eq!(loaded.log, save.log);
+    assert_eq!(loaded.timer_ms, 500);
+    assert_eq!(loaded.inventory, save.inventory);
+    assert_eq!((loaded.seed, loaded.rng_position), (Some(7), u64::MAX as u128 + 3));
+    assert_eq!(saves.slots(), vec!["1"]);
+}
+
+#[test]
+fn test_latest_slot_wins() {
+    let (_dir, saves) = test_saves("latest");
+    saves.write("old", &test_save(10)).unwrap();
+    saves.write("new", &test_save(20)).unwrap();
+    let (slot, save) = saves.latest().0.unwrap();
+    assert_eq!(slot, "new");
+    assert_eq!(save.saved_at, 20);
+    // a seeded replay is never picked up on startup
+    saves.write(&seeded_slot(7), &test_save(30)).unwrap();
+    assert_eq!(saves.latest().0.unwrap().0, "new");
+}
+
+#[test]
+fn test_latest_reports_slots_it_cannot_read() {
+    let (_dir, saves) = test_saves("unreadable");
+    saves.write("old", &test_save(10)).unwrap();
+    let mut future = test_save(20);
+    future.version = SAVE_VERSION + 1;
+    saves.write(AUTOSAVE_SLOT, &future).unwrap();
+
+    let (latest, failed) = saves.latest();
+    assert_eq!(latest.unwrap().0, "old");
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].0, AUTOSAVE_SLOT);
+    assert!(format!("{:#}", failed[0].1).contains("newer version"));
+}
+
+#[test]
+fn test_rejects_bad_slots_and_versions() {
+    let (_dir, saves) = test_saves("reject");
+    assert!(saves.write("../x", &test_save(0)).is_err());
+    assert!(saves.read("missing").is_err());
+    let mut save = test_save(0);
+    save.version = SAVE_VERSION + 1;
+    saves.write("future", &save).unwrap();
+    assert!(saves.read("future").is_err());
+    // anything but a missing file says what went wrong
+    fs::create_dir_all(saves.path("dir").unwrap()).unwrap();
+    let error = format!("{:#}", saves.read("dir").unwrap_err());
+    assert!(!error.contains("no save"), "{}", error);
+}
+
+#[test]
+fn test_old_versions_fill_in_defaults() {
+    let (_dir, saves) = test_saves("migrate");
+    let mut json: serde_json::Value =
+        serde_json::from_str(&serde_json::to_string(&test_save(0)).unwrap()).unwrap();
+    json["version"] = 3.into();
+    for field in ["inventory", "seed", "rng_position"] {
+        json.as_object_mut().unwrap().remove(field);
+    }
+    let hero = &mut json["heroes"][0];
+    hero.as_object_mut().unwrap().remove("equipment");
+    for stat in ["crit_chance", "crit_damage", "accuracy", "evasion", "resistances"] {
+        hero["stat"].as_object_mut().unwrap().remove(stat);
+    }
+    fs::create_dir_all(&saves.dir).unwrap();
+    fs::write(saves.path("old").unwrap(), json.to_string()).unwrap();
+
+    let save = saves.read("old").unwrap();
+    assert_eq!(save.version, SAVE_VERSION);
+    assert_eq!(save.inventory, Inventory::default());
+    assert_eq!(save.seed, None);
+    let stat = &save.heroes[0].borrow().stat;
+    assert_eq!((stat.crit_damage, stat.accuracy, stat.evasion), (50, 100, 0));
+    assert!(save.heroes[0].borrow().equipment.weapon.is_none());
+}
+
+#[test]
+fn test_colliding_ids_are_fixed_on_load() {
+    let (_dir, saves) = test_saves("ids");
+    let save = test_save(0);
+    let id = save.heroes[0].borrow().id;
+    save.enemies[0].borrow_mut().set_id(id);
+    saves.write("old", &save).unwrap();
+
+    let mut loaded = saves.read("old").unwrap();
+    let enemy_id = loaded.enemies[0].borrow().id();
+    assert_eq!(loaded.heroes[0].borrow().id, id);
+    assert_ne!(enemy_id, id);
+    let next = loaded.ids.allocate();
+    assert!(next > id && next > enemy_id);
+}
@@ -0,0 +1,379 @@
+use std::{cell::RefCell, collections::BTreeMap, fmt::Display, path::PathBuf, rc::Rc};
+
+use anyhow::{anyhow, bail, Context, Result};
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+
+use super::{
+    combat,
+    data::Catalog,
+    encounter::{self, Encounter, Outcome},
+    entities::{enemy::Enemy, hero::Hero, Entity, IdAllocator, SharedEntity},
+    timeline::Timeline,
+    world::{GameRng, World},
+};
+
+// headless battles for balancing, the same engine offline progress runs on, no terminal involved
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setup {
+    // class ids
+    pub party: Vec<String>,
+    pub level: u32,
+    // archetype ids, left empty the encounter spawns its usual wave
+    pub enemies: Vec<String>,
+    pub stage: u32,
+    pub wave: u32,
+    pub battles: u32,
+    pub seed: u64,
+    // battles still going after this many turns count as draws
+    pub max_turns: u32,
+    // where the definitions overriding the bundled ones are, the game's own data folder by default
+    pub data: Option<PathBuf>,
+    pub json: bool,
+}
+
+impl Default for Setup {
+    fn default() -> Self {
+        Self {
+            party: vec!["warrior".to_owned(), "mage".to_owned(), "rogue".to_owned()],
+            level: 1,
+            enemies: Vec::new(),
+            stage: 1,
+            wave: 1,
+            battles: 100,
+            seed: 0,
+            max_turns: 500,
+            data: None,
+            json: false,
+        }
+    }
+}
+
+pub const USAGE: &str = "usage: termquest-sim [--battles N] [--seed N] [--party warrior,mage,..] [--level N]
+                     [--enemies goblin,slime,..] [--stage N] [--wave N] [--max-turns N]
+                     [--data DIR] [--json]";
+
+impl Setup {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut setup = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--json" {
+                setup.json = true;
+                continue;
+            }
+            let value = args.next().ok_or_else(|| anyhow!("{} needs a value", arg))?;
+            let number = || -> Result<u64> {
+                value
+                    .parse()
+                    .with_context(|| format!("{} expects a number, got '{}'", arg, value))
+            };
+            let list = || -> Vec<String> {
+                value
+                    .split(',')
+                    .map(|s| s.trim().to_owned())
+                    .filter(|s| !s.is_empty())
+                    .collect()
+            };
+            match arg.as_str() {
+                "--battles" => setup.battles = number()? as u32,
+                "--seed" => setup.seed = number()?,
+                "--party" => setup.party = list(),
+                "--level" => setup.level = number()? as u32,
+                "--enemies" => setup.enemies = list(),
+                "--stage" => setup.stage = number()? as u32,
+                "--wave" => setup.wave = number()? as u32,
+                "--max-turns" => setup.max_turns = number()? as u32,
+                "--data" => setup.data = Some(PathBuf::from(value)),
+                _ => bail!("unknown option '{}'\n{}", arg, USAGE),
+            }
+        }
+        if setup.party.is_empty() {
+            bail!("the party needs at least one class");
+        }
+        if setup.stage == 0 || !(1..=encounter::WAVES_PER_STAGE).contains(&setup.wave) {
+            bail!("stages start at 1, waves go from 1 to {}", encounter::WAVES_PER_STAGE);
+        }
+        Ok(setup)
+    }
+
+    fn encounter(&self) -> Encounter {
+        Encounter {
+            stage: self.stage,
+            wave: self.wave,
+            checkpoint: 1,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct EntityReport {
+    pub name: String,
+    pub side: String,
+    pub battles: u32,
+    pub damage_dealt: u64,
+    pub survived: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub battles: u32,
+    pub seed: u64,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    pub win_rate: f64,
+    pub avg_turns: f64,
+    pub entities: Vec<EntityReport>,
+    // how many battles ended with that many heroes standing
+    pub heroes_standing: Vec<u32>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let share = |n: u32| n as f64 * 100. / self.battles.max(1) as f64;
+        writeln!(f, "{} battles, seed {}", self.battles, self.seed)?;
+        writeln!(
+            f,
+            "win rate  {:.1}% ({} won, {} lost, {} drawn)",
+            self.win_rate * 100.,
+            self.wins,
+            self.losses,
+            self.draws
+        )?;
+        writeln!(f, "avg turns {:.1}", self.avg_turns)?;
+        writeln!(f)?;
+        writeln!(f, "{:<20} {:<6} {:>12} {:>9}", "entity", "side", "dmg/battle", "survived")?;
+        for entity in &self.entities {
+            writeln!(
+                f,
+                "{:<20} {:<6} {:>12.1} {:>8.1}%",
+                entity.name,
+                entity.side,
+                entity.damage_dealt as f64 / entity.battles.max(1) as f64,
+                entity.survived as f64 * 100. / entity.battles.max(1) as f64
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(f, "heroes standing")?;
+        for (standing, battles) in self.heroes_standing.iter().enumerate() {
+            let share = share(*battles);
+            writeln!(f, "{:>3} {:>6.1}% {}", standing, share, "#".repeat((share / 2.).round() as usize))?;
+        }
+        Ok(())
+    }
+}
+
+struct Battle {
+    outcome: Option<Outcome>,
+    turns: u32,
+    // name, side, damage dealt, still standing
+    entities: Vec<(String, &'static str, u64, bool)>,
+}
+
+fn total_hp(side: &[SharedEntity]) -> u64 {
+    side.iter().map(|e| e.borrow().stat().curr_hp as u64).sum()
+}
+
+fn party(setup: &Setup, ids: &mut IdAllocator, catalog: &Catalog) -> Result<Vec<Hero>> {
+    let mut heroes: Vec<Hero> = Vec::new();
+    for class in &setup.party {
+        let class = catalog.class(class)?;
+        let mut hero = Hero::from_class(ids, catalog, class);
+        while hero.progress.level < setup.level {
+            let needed = hero.progress.xp_to_next() - hero.progress.xp;
+            hero.gain_xp(needed);
+        }
+        // the same naming the waves use, so two of a class stay apart in the report
+        let same = heroes.iter().filter(|h| h.class == hero.class).count();
+        if same > 0 {
+            hero.info.name = format!("{} {}", class.name, same + 1).into();
+        }
+        heroes.push(hero);
+    }
+    Ok(heroes)
+}
+
+fn enemies(setup: &Setup, ids: &mut IdAllocator, catalog: &Catalog, rng: &mut impl Rng) -> Result<Vec<Enemy>> {
+    let encounter = setup.encounter();
+    if setup.enemies.is_empty() {
+        return Ok(encounter.spawn(ids, catalog, rng));
+    }
+    let mut enemies: Vec<Enemy> = Vec::new();
+    for id in &setup.enemies {
+        let archetype = catalog.archetype(id)?;
+        let mut enemy = Enemy::from_archetype(ids, catalog, archetype, encounter.enemy_level());
+        let same = enemies.iter().filter(|e| e.archetype() == archetype.id).count();
+        if same > 0 {
+            enemy.rename(format!("{} {}", archetype.name, same + 1));
+        }
+        enemies.push(enemy);
+    }
+    Ok(enemies)
+}
+
+// every battle gets its own world and seed, like a fresh game would
+fn battle(setup: &Setup, catalog: &Catalog, seed: u64) -> Result<Battle> {
+    let mut ids = IdAllocator::default();
+    let world = Rc::new(RefCell::new(World::seeded(seed)));
+    for hero in party(setup, &mut ids, catalog)? {
+        world.borrow_mut().add_hero(hero);
+    }
+    let enemies = enemies(setup, &mut ids, catalog, &mut *world.borrow().rng())?;
+    world.borrow_mut().set_enemies(enemies);
+    let (heroes, foes) = (world.borrow().heroes_dyn(), world.borrow().enemies_dyn());
+    let mut timeline = Timeline::new(world.clone());
+    timeline.sync();
+
+    // whatever the opponents lose during someone's turn is on them, damage over time
+    // ticks on the victim's own turn and isn't credited to anyone
+    let mut dealt: BTreeMap<_, u64> = BTreeMap::new();
+    let mut turns = 0;
+    let outcome = loop {
+        if let Some(outcome) = encounter::outcome(&heroes, &foes) {
+            break Some(outcome);
+        }
+        let Some(id) = timeline.current() else {
+            break None;
+        };
+        if turns >= setup.max_turns {
+            break None;
+        }
+        let opponents = match world.borrow().get(id) {
+            Some(actor) => world.borrow().sides(actor.borrow().get_type()).1,
+            None => Vec::new(),
+        };
+        let before = total_hp(&opponents);
+        combat::take_turn(id, &world.borrow(), None);
+        *dealt.entry(id).or_default() += before.saturating_sub(total_hp(&opponents));
+        timeline.pop();
+        turns += 1;
+    };
+
+    let entities = heroes
+        .iter()
+        .map(|e| (e, "party"))
+        .chain(foes.iter().map(|e| (e, "enemy")))
+        .map(|(e, side)| {
+            let e = e.borrow();
+            let dealt = dealt.get(&e.id()).copied().unwrap_or(0);
+            (e.info().name.to_string(), side, dealt, e.is_alive())
+        })
+        .collect();
+    Ok(Battle { outcome, turns, entities })
+}
+
+pub fn run(setup: &Setup) -> Result<Report> {
+    let catalog = match &setup.data {
+        Some(dir) => Catalog::load_from(Some(dir))?,
+        None => Catalog::load()?,
+    };
+    simulate(setup, &catalog)
+}
+
+pub fn simulate(setup: &Setup, catalog: &Catalog) -> Result<Report> {
+    let mut seeds = GameRng::seed_from_u64(setup.seed);
+    let mut report = Report {
+        battles: setup.battles,
+        seed: setup.seed,
+        heroes_standing: vec![0; setup.party.len() + 1],
+        ..Default::default()
+    };
+    let mut turns = 0;
+    for _ in 0..setup.battles {
+        let battle = battle(setup, catalog, seeds.gen())?;
+        match battle.outcome {
+            Some(Outcome::Victory) => report.wins += 1,
+            Some(Outcome::Defeat) => report.losses += 1,
+            None => report.draws += 1,
+        }
+        turns += battle.turns as u64;
+        let standing = battle
+            .entities
+            .iter()
+            .filter(|(_, side, _, alive)| *side == "party" && *alive)
+            .count();
+        report.heroes_standing[standing] += 1;
+        for (name, side, dealt, alive) in battle.entities {
+            let index = match report.entities.iter().position(|e| e.name == name && e.side == side) {
+                Some(index) => index,
+                None => {
+                    report.entities.push(EntityReport {
+                        name,
+                        side: side.to_owned(),
+                        ..Default::default()
+                    });
+                    report.entities.len() - 1
+                }
+            };
+            let entity = &mut report.entities[index];
+            entity.battles += 1;
+            entity.damage_dealt += dealt;
+            entity.survived += alive as u32;
+        }
+    }
+    let battles = setup.battles.max(1) as f64;
+    report.win_rate = report.wins as f64 / battles;
+    report.avg_turns = turns as f64 / battles;
+    Ok(report)
+}
+
+#[cfg(test)]
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(|s| s.to_owned()).collect()
+}
+
+#[test]
+fn test_parse_setup() {
+    let setup = Setup::parse(args("--battles 20 --seed 7 --party warrior,cleric --enemies slime --json")).unwrap();
+    assert_eq!(setup.battles, 20);
+    assert_eq!(setup.seed, 7);
+    assert_eq!(setup.party, ["warrior", "cleric"]);
+    assert_eq!(setup.enemies, ["slime"]);
+    assert!(setup.json);
+    assert_eq!(setup.level, 1);
+
+    assert!(Setup::parse(args("--battles")).is_err());
+    assert!(Setup::parse(args("--battles many")).is_err());
+    assert!(Setup::parse(args("--speed 2")).is_err());
+    assert!(Setup::parse(args("--wave 4")).is_err());
+}
+
+#[test]
+fn test_simulate_reports_every_battle() {
+    let catalog = Catalog::bundled();
+    let setup = Setup {
+        party: vec!["warrior".to_owned(), "warrior".to_owned()],
+        level: 5,
+        enemies: vec!["goblin".to_owned()],
+        battles: 10,
+        ..Default::default()
+    };
+    let report = simulate(&setup, &catalog).unwrap();
+    assert_eq!(report.wins + report.losses + report.draws, 10);
+    assert_eq!(report.wins, 10);
+    assert!(report.avg_turns > 0.);
+    assert_eq!(report.heroes_standing.iter().sum::<u32>(), 10);
+    let names: Vec<&str> = report.entities.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["Warrior", "Warrior 2", "Goblin"]);
+    let goblin = &report.entities[2];
+    assert_eq!((goblin.battles, goblin.survived), (10, 0));
+    assert!(report.entities[..2].iter().map(|e| e.damage_dealt).sum::<u64>() > 0);
+    assert!(report.to_string().contains("win rate  100.0% (10 won, 0 lost, 0 drawn)"));
+
+    // the same seed plays out the same, random spawns and random policies included
+    let seeded = Setup {
+        enemies: Vec::new(),
+        stage: 2,
+        seed: 11,
+        ..Default::default()
+    };
+    assert_eq!(simulate(&seeded, &catalog).unwrap(), simulate(&seeded, &catalog).unwrap());
+
+    let unknown = Setup {
+        party: vec!["bard".to_owned()],
+        ..Default::default()
+    };
+    assert!(simulate(&unknown, &catalog).is_err());
+}
@@ -0,0 +1,54 @@
+use std::collections::VecDeque;
+
+// older lines fall off the top, the pane only ever shows the last few anyway
+pub const MAX_LOG: usize = 200;
+
+#[derive(Debug, Default)]
+pub struct Log {
+    lines: VecDeque<String>,
+}
+
+impl Log {
+    pub fn push(&mut self, line: String) {
+        self.lines.push_back(line);
+        if self.lines.len() > MAX_LOG {
+            self.lines.pop_front();
+        }
+    }
+
+    // the newest n lines, oldest first
+    pub fn tail(&self, n: usize) -> impl Iterator<Item = &String> {
+        self.lines.iter().skip(self.lines.len().saturating_sub(n))
+    }
+
+    pub fn to_vec(&self) -> Vec<String> {
+        self.lines.iter().cloned().collect()
+    }
+}
+
+impl Extend<String> for Log {
+    fn extend<T: IntoIterator<Item = String>>(&mut self, lines: T) {
+        for line in lines {
+            self.push(line);
+        }
+    }
+}
+
+// saves from before the cap can carry any number of lines
+impl From<Vec<String>> for Log {
+    fn from(lines: Vec<String>) -> Self {
+        let mut log = Log::default();
+        log.extend(lines);
+        log
+    }
+}
+
+#[test]
+fn test_log_keeps_the_newest_lines() {
+    let mut log = Log::from((0..MAX_LOG + 5).map(|i| i.to_string()).collect::<Vec<_>>());
+    assert_eq!(log.to_vec().len(), MAX_LOG);
+    assert_eq!(log.to_vec()[0], "5");
+    log.push("last".to_owned());
+    let tail: Vec<String> = log.tail(2).cloned().collect();
+    assert_eq!(tail, vec![(MAX_LOG + 4).to_string(), "last".to_owned()]);
+}
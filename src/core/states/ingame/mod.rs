@@ -1,25 +1,40 @@
-use std::{rc::Rc, time::{Duration, Instant}};
+use std::{cell::RefCell, rc::Rc, time::{Duration, Instant}};
 
 #[cfg(not(target_arch = "wasm32"))]
 use crossterm::event::{KeyCode, KeyModifiers};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::Stylize,
-    widgets::{Block, Borders, List},
+    widgets::{Block, Borders, List, Paragraph},
     Frame,
 };
 
+use anyhow::{anyhow, bail, Result};
+
 use crate::{
     core::{
-        components::{Command, EntityList, Turn, Component},
-        consts::{ACCENT, PRIMARY},
-        entities::{enemy::Enemy, hero::Hero, Entity, Id},
+        combat::{self, Action},
+        data::Catalog,
+        encounter::{self, Encounter, Outcome},
+        commands::{ArgKind, ArgSpec, Args, CommandSpec, Registry, Value},
+        offline,
+        components::{Command, Details, EntityList, InventoryView, Menu, Turn, Component},
+        history::History,
+        consts::{ACCENT, PRIMARY, TURN_DELAY},
+        entities::{enemy::Enemy, hero::Hero, Entity, Id, IdAllocator, SharedEntity},
+        save::{self, SaveGame, Saves, Unreadable, AUTOSAVE_SLOT, SAVE_VERSION},
+        timeline::{SharedTimeline, Timeline},
+        types::{Slot, Target},
+        world::{SharedWorld, World},
     },
     Context, Event,
 };
 
 use super::{State, StateType};
 
+mod log;
+use log::Log;
+
 #[derive(PartialEq, Clone, Copy)]
 enum StateWidget {
     Hero,
@@ -27,6 +42,8 @@ enum StateWidget {
     Turn,
     Log,
     Command,
+    Menu,
+    Inventory,
 }
 
 enum NavDirection {
@@ -36,31 +53,457 @@ enum NavDirection {
     Left,
 }
 
+#[derive(Clone, Copy)]
+enum Choice {
+    Attack,
+    Skill(usize),
+}
+
+// a hero waiting for the player in manual mode
+struct Orders {
+    hero: Id,
+    since: Instant,
+    choice: Option<Choice>,
+    previous_widget: StateWidget,
+}
+
 pub struct InGame {
+    world: SharedWorld,
     heroes: EntityList<Hero>,
     enemies: EntityList<Enemy>,
     current_turn: Option<Id>,
     selected_widget: StateWidget,
     is_in_a_widget: bool,
     command: Command,
-    log: Vec<String>,
+    log: Log,
     turn: Turn,
+    timeline: SharedTimeline,
     timer: Instant,
+    saves: Saves,
+    autosave: String,
+    commands: Registry<InGame>,
+    focus: Option<Id>,
+    speed: u32,
+    catalog: Rc<Catalog>,
+    ids: IdAllocator,
+    manual: bool,
+    manual_timeout: Duration,
+    orders: Option<Orders>,
+    menu: Menu,
+    inventory: InventoryView,
+    details: Details,
+    encounter: Encounter,
+    // how the last battle ended and when, the next wave comes after the pause
+    resolved: Option<(Outcome, Instant)>,
+    battle_pause: Duration,
 }
 
 impl InGame {
     pub fn new() -> Self {
+        let world = World::shared();
+        let timeline = Timeline::shared(world.clone());
         InGame {
-            heroes: EntityList::new(),
-            enemies: EntityList::new(),
+            heroes: EntityList::new(world.clone()),
+            enemies: EntityList::new(world.clone()),
+            turn: Turn::new(timeline.clone()),
+            timeline,
+            world,
             current_turn: None,
             selected_widget: StateWidget::Command,
             is_in_a_widget: false,
             command: Command::new(),
-            log: Vec::new(),
-            turn: Turn::new(),
-            timer: Instant::now()
+            log: Log::default(),
+            timer: Instant::now(),
+            saves: Saves::new(),
+            autosave: AUTOSAVE_SLOT.to_owned(),
+            commands: Self::commands(),
+            focus: None,
+            speed: 1,
+            catalog: Rc::default(),
+            ids: IdAllocator::default(),
+            manual: false,
+            manual_timeout: Duration::ZERO,
+            orders: None,
+            menu: Menu::new(),
+            inventory: InventoryView::new(),
+            details: Details::new(),
+            encounter: Encounter::default(),
+            resolved: None,
+            battle_pause: Duration::ZERO,
+        }
+    }
+
+    fn commands() -> Registry<Self> {
+        Registry::new()
+            .register(
+                CommandSpec::new("help", "list commands or describe one", Self::cmd_help)
+                    .arg(ArgSpec::optional("command", ArgKind::Command)),
+            )
+            .register(
+                CommandSpec::new("save", "save the game to a slot", Self::cmd_save)
+                    .arg(ArgSpec::optional("slot", ArgKind::Slot)),
+            )
+            .register(
+                CommandSpec::new("load", "load a slot, or list them", Self::cmd_load)
+                    .arg(ArgSpec::optional("slot", ArgKind::Slot)),
+            )
+            .register(
+                CommandSpec::new("attack", "current hero attacks an enemy now", Self::cmd_attack)
+                    .alias("a")
+                    .arg(ArgSpec::required("enemy", ArgKind::Entity)),
+            )
+            .register(
+                CommandSpec::new("target", "select an entity, heroes focus enemies", Self::cmd_target)
+                    .alias("t")
+                    .arg(ArgSpec::required("side", ArgKind::Choice(&["hero", "enemy"])))
+                    .arg(ArgSpec::required("entity", ArgKind::Entity)),
+            )
+            .register(
+                CommandSpec::new("speed", "set the battle speed", Self::cmd_speed)
+                    .arg(ArgSpec::required("speed", ArgKind::Multiplier { max: 8 })),
+            )
+            .register(
+                CommandSpec::new("mode", "let heroes fight on their own or wait for orders", Self::cmd_mode)
+                    .arg(ArgSpec::optional("mode", ArgKind::Choice(&["auto", "manual"]))),
+            )
+            .register(
+                CommandSpec::new("unequip", "put the selected hero's gear back in the inventory", Self::cmd_unequip)
+                    .arg(ArgSpec::required("slot", ArgKind::Choice(&["weapon", "armor", "accessory"]))),
+            )
+            .register(
+                CommandSpec::new("quit", "save and quit", Self::cmd_quit)
+                    .alias("q")
+                    .alias("exit"),
+            )
+    }
+
+    fn cmd_help(&mut self, args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        self.log.extend(self.commands.help(args.text("command"))?);
+        Ok(None)
+    }
+
+    fn cmd_save(&mut self, args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        let slot = args.text("slot").unwrap_or(&self.autosave).to_owned();
+        self.save(&slot);
+        Ok(None)
+    }
+
+    fn cmd_load(&mut self, args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        match args.text("slot") {
+            Some(slot) => self.load(slot),
+            None => {
+                let slots = self.saves.slots();
+                if slots.is_empty() {
+                    self.log.push("No saves yet".to_owned());
+                } else {
+                    self.log.push(format!("Slots: {}", slots.join(", ")));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn cmd_attack(&mut self, args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        let (_, target) = Self::lookup(&self.enemies_dyn(), args.get("enemy"))?;
+        if !target.borrow().is_alive() {
+            bail!("{} is already defeated", target.borrow().info().name);
+        }
+        let Some(hero) = self.current_hero() else {
+            bail!("it's not a hero's turn");
+        };
+        let id = target.borrow().id();
+        if self.orders.is_some() {
+            let action = combat::attack(&*hero.borrow(), &*target.borrow());
+            self.give_order(action);
+        } else {
+            self.step(Some(id));
+        }
+        Ok(None)
+    }
+
+    fn cmd_mode(&mut self, args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        let manual = match args.text("mode") {
+            Some(mode) => mode == "manual",
+            None => !self.manual,
+        };
+        self.set_manual(manual);
+        Ok(None)
+    }
+
+    fn set_manual(&mut self, manual: bool) {
+        self.manual = manual;
+        if manual {
+            self.log.push(format!(
+                "Manual mode, heroes wait {}s for orders",
+                self.manual_timeout.as_secs()
+            ));
+        } else {
+            // whoever was waiting goes back to the auto policy on the next update
+            self.close_orders();
+            self.log.push("Auto mode".to_owned());
+        }
+    }
+
+    fn is_alive(&self, id: Id) -> bool {
+        self.heroes_dyn()
+            .iter()
+            .chain(&self.enemies_dyn())
+            .any(|e| e.borrow().id() == id && e.borrow().is_alive())
+    }
+
+    fn current_hero(&self) -> Option<Rc<RefCell<Hero>>> {
+        let id = self.current_turn?;
+        self.world.borrow().hero(id)
+    }
+
+    fn exit_widget(&mut self) {
+        match self.selected_widget {
+            StateWidget::Hero => self.heroes.exit(),
+            StateWidget::Enemy => self.enemies.exit(),
+            StateWidget::Turn => self.turn.exit(),
+            StateWidget::Log => (),
+            StateWidget::Command => self.command.exit(),
+            StateWidget::Menu => self.menu.exit(),
+            StateWidget::Inventory => self.inventory.exit(),
+        }
+        self.is_in_a_widget = false;
+    }
+
+    fn focus_widget(&mut self, widget: StateWidget) {
+        self.exit_widget();
+        self.selected_widget = widget;
+        match widget {
+            StateWidget::Hero => self.heroes.enter(),
+            StateWidget::Enemy => self.enemies.enter(),
+            StateWidget::Menu => self.menu.enter(),
+            _ => (),
+        }
+        self.is_in_a_widget = true;
+    }
+
+    // pauses the battle until the player picks an action or the timeout runs out
+    fn open_orders(&mut self) {
+        let Some(hero) = self.current_hero() else {
+            return;
+        };
+        let hero = hero.borrow();
+        let mut items = vec!["Attack".to_owned()];
+        items.extend(hero.skills.iter().map(|slot| slot.to_string()));
+        self.menu.set_items(items);
+        self.orders = Some(Orders {
+            hero: hero.id,
+            since: Instant::now(),
+            choice: None,
+            previous_widget: self.selected_widget,
+        });
+        self.focus_widget(StateWidget::Menu);
+    }
+
+    fn close_orders(&mut self) {
+        if let Some(orders) = self.orders.take() {
+            self.exit_widget();
+            self.selected_widget = orders.previous_widget;
+        }
+    }
+
+    fn pick_choice(&mut self) {
+        let (Some(hero), Some(idx)) = (self.current_hero(), self.menu.selected()) else {
+            return;
+        };
+        let hero = hero.borrow();
+        let (choice, target) = match idx.checked_sub(1) {
+            None => (Choice::Attack, Target::Enemy),
+            Some(skill) => {
+                let slot = &hero.skills[skill];
+                if !slot.usable_by(&hero.stat) {
+                    self.log.push(format!("{} can't use {} yet", hero.info().name, slot.skill.name()));
+                    return;
+                }
+                (Choice::Skill(skill), slot.skill.target())
+            }
+        };
+        let widget = match target {
+            Target::Enemy => StateWidget::Enemy,
+            Target::Ally => StateWidget::Hero,
+            _ => {
+                let id = hero.id;
+                drop(hero);
+                if let Choice::Skill(skill) = choice {
+                    self.give_order(Action::Skill { skill, target: id });
+                }
+                return;
+            }
+        };
+        if let Some(orders) = &mut self.orders {
+            orders.choice = Some(choice);
+        }
+        let label = match choice {
+            Choice::Attack => "Attack",
+            Choice::Skill(skill) => hero.skills[skill].skill.name(),
+        };
+        self.log.push(format!("Pick a target for {}", label));
+        drop(hero);
+        self.focus_widget(widget);
+    }
+
+    fn pick_target(&mut self) {
+        let (Some(hero), Some(choice)) = (
+            self.current_hero(),
+            self.orders.as_ref().and_then(|o| o.choice),
+        ) else {
+            return;
+        };
+        let target: Option<SharedEntity> = match self.selected_widget {
+            StateWidget::Enemy => self.enemies.selected().map(|e| e as SharedEntity),
+            StateWidget::Hero => self.heroes.selected().map(|e| e as SharedEntity),
+            _ => None,
+        };
+        let Some(target) = target else {
+            return;
+        };
+        let revives = match choice {
+            Choice::Skill(skill) => hero.borrow().skills[skill].skill.revives(),
+            Choice::Attack => false,
+        };
+        let target_name = target.borrow().info().name.clone();
+        match (target.borrow().is_alive(), revives) {
+            (false, false) => {
+                self.log.push(format!("{} is already defeated", target_name));
+                return;
+            }
+            (true, true) => {
+                self.log.push(format!("{} is still standing", target_name));
+                return;
+            }
+            _ => (),
+        }
+        let action = match choice {
+            Choice::Attack => combat::attack(&*hero.borrow(), &*target.borrow()),
+            Choice::Skill(skill) => Action::Skill {
+                skill,
+                target: target.borrow().id(),
+            },
+        };
+        self.give_order(action);
+    }
+
+    fn give_order(&mut self, action: Action) {
+        let Some(orders) = &self.orders else {
+            return;
+        };
+        let id = orders.hero;
+        self.close_orders();
+        self.timer = Instant::now();
+        let lines = combat::take_ordered_turn(id, &self.world.borrow(), action);
+        self.log.extend(lines);
+        self.advance();
+    }
+
+    // esc on a target list goes back to the menu, esc on the menu lets the hero decide
+    fn cancel_order(&mut self) {
+        let Some(orders) = &mut self.orders else {
+            return;
+        };
+        if orders.choice.take().is_some() {
+            self.focus_widget(StateWidget::Menu);
+        } else {
+            self.act_on_its_own();
+        }
+    }
+
+    fn act_on_its_own(&mut self) {
+        if let Some(hero) = self.current_hero() {
+            self.log.push(format!("{} acts on its own", hero.borrow().info().name));
+        }
+        self.close_orders();
+        self.step(self.focus);
+    }
+
+    fn cmd_target(&mut self, args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        if args.text("side") == Some("hero") {
+            let (idx, _) = Self::lookup(&self.heroes_dyn(), args.get("entity"))?;
+            self.heroes.select(idx);
+        } else {
+            let (idx, target) = Self::lookup(&self.enemies_dyn(), args.get("entity"))?;
+            self.enemies.select(idx);
+            self.focus = Some(target.borrow().id());
+            self.log.push(format!("Heroes focus {}", target.borrow().info().name));
+        }
+        Ok(None)
+    }
+
+    fn cmd_unequip(&mut self, args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        let slot = Slot::ALL
+            .into_iter()
+            .find(|s| args.text("slot") == Some(s.to_string().as_str()))
+            .ok_or_else(|| anyhow!("no slot given"))?;
+        let hero = self.heroes.selected().ok_or_else(|| anyhow!("no hero selected"))?;
+        let line = self.inventory.inventory.unequip(&mut hero.borrow_mut(), slot)?;
+        self.log.push(line);
+        Ok(None)
+    }
+
+    // the hovered item goes on the hero selected in the heroes list
+    fn equip_selected(&mut self) {
+        let (Some(hero), Some(item)) = (self.heroes.selected(), self.inventory.selected().cloned()) else {
+            return;
+        };
+        let result = self.inventory.inventory.equip(&mut hero.borrow_mut(), &item);
+        self.log.push(result.unwrap_or_else(|e| e.to_string()));
+    }
+
+    fn lookup(list: &[SharedEntity], value: Option<&Value>) -> Result<(usize, SharedEntity)> {
+        let idx = match value {
+            Some(Value::Int(n)) => (*n as usize)
+                .checked_sub(1)
+                .filter(|idx| *idx < list.len())
+                .ok_or_else(|| anyhow!("there is no entity number {}", n))?,
+            Some(Value::Text(name)) => list
+                .iter()
+                .position(|e| e.borrow().info().name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| anyhow!("there is no entity named '{}'", name))?,
+            _ => bail!("no entity given"),
+        };
+        Ok((idx, list[idx].clone()))
+    }
+
+    // first tab completes, further tabs cycle through the candidates
+    fn complete(&mut self, forward: bool) {
+        if self.command.cycle_completion(forward) {
+            return;
         }
+        let completion = self
+            .commands
+            .complete(self.command.before_cursor(), &|kind, args| self.candidates(kind, args));
+        self.command.complete(completion);
+    }
+
+    fn candidates(&self, kind: ArgKind, args: &[String]) -> Vec<String> {
+        let names = |list: Vec<SharedEntity>| {
+            list.iter()
+                .map(|e| e.borrow().info().name.to_string())
+                .collect()
+        };
+        match kind {
+            ArgKind::Slot => self.saves.slots(),
+            ArgKind::Entity if args.iter().any(|a| a.eq_ignore_ascii_case("hero")) => {
+                names(self.heroes_dyn())
+            }
+            ArgKind::Entity => names(self.enemies_dyn()),
+            _ => Vec::new(),
+        }
+    }
+
+    fn cmd_speed(&mut self, args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        self.speed = args.multiplier("speed").unwrap_or(1);
+        self.log.push(format!("Speed set to {}x", self.speed));
+        Ok(None)
+    }
+
+    fn cmd_quit(&mut self, _args: &Args, ctx: &mut Context) -> Result<Option<StateType>> {
+        self.autosave();
+        ctx.should_quit = true;
+        Ok(None)
     }
 
     fn handle_nav(&mut self, e: &Event) {
@@ -90,6 +533,7 @@ impl InGame {
                 match self.selected_widget {
                     StateWidget::Command => Some(StateWidget::Log),
                     StateWidget::Log     => Some(StateWidget::Hero),
+                    StateWidget::Inventory => Some(StateWidget::Enemy),
                     _ => None,
                 }
             }
@@ -99,6 +543,7 @@ impl InGame {
                     StateWidget::Hero  => Some(StateWidget::Log),
                     StateWidget::Enemy => Some(StateWidget::Log),
                     StateWidget::Turn  => Some(StateWidget::Log),
+                    StateWidget::Inventory => Some(StateWidget::Command),
                     _ => None,
                 }
             }
@@ -106,6 +551,7 @@ impl InGame {
                 match self.selected_widget {
                     StateWidget::Hero => Some(StateWidget::Turn),
                     StateWidget::Turn => Some(StateWidget::Enemy),
+                    StateWidget::Log  => Some(StateWidget::Inventory),
                     _ => None,
                 }
             }
@@ -113,6 +559,7 @@ impl InGame {
                 match self.selected_widget {
                     StateWidget::Enemy => Some(StateWidget::Turn),
                     StateWidget::Turn  => Some(StateWidget::Hero),
+                    StateWidget::Inventory => Some(StateWidget::Log),
                     _ => None,
                 }
             }
@@ -122,47 +569,269 @@ impl InGame {
             self.selected_widget = next_wid;
         }
     }
-}
 
-impl State for InGame {
-    fn init(&mut self) {
-        self.heroes.entities.push(Rc::new(Hero::new()));
-        self.heroes.entities.push(Rc::new(Hero::new()));
-        self.heroes.entities.push(Rc::new(Hero::new()));
-        self.enemies.entities.push(Rc::new(Enemy::new()));
-        self.enemies.entities.push(Rc::new(Enemy::new()));
-        self.enemies.entities.push(Rc::new(Enemy::new()));
+    fn heroes_dyn(&self) -> Vec<SharedEntity> {
+        self.world.borrow().heroes_dyn()
+    }
 
-        let mut entities: Vec<Rc<dyn Entity>> = Vec::new();
-        for (h, e) in self.heroes.entities.iter().zip(self.enemies.entities.iter()) {
-            entities.push(h.clone());
-            entities.push(e.clone());
-        }
+    fn enemies_dyn(&self) -> Vec<SharedEntity> {
+        self.world.borrow().enemies_dyn()
+    }
 
-        self.turn.set_entities(entities);
+    fn step(&mut self, focus: Option<Id>) {
+        self.timer = Instant::now();
+        if let Some(id) = self.current_turn {
+            let lines = combat::take_turn(id, &self.world.borrow(), focus);
+            self.log.extend(lines);
+        }
+        self.advance();
+    }
 
-        self.current_turn = self.turn.get_current_turn();
+    fn advance(&mut self) {
+        self.current_turn = self.timeline.borrow_mut().pop();
         self.heroes.set_turn(self.current_turn);
         self.enemies.set_turn(self.current_turn);
+    }
+
+    fn new_game(&mut self) {
+        for class in self.catalog.classes.iter().filter(|c| c.starter) {
+            let hero = Hero::from_class(&mut self.ids, &self.catalog, class);
+            self.world.borrow_mut().add_hero(hero);
+        }
+        self.spawn_wave();
+        self.reset_turn();
+        self.timer = Instant::now();
+    }
+
+    fn spawn_wave(&mut self) {
+        let enemies = self
+            .encounter
+            .spawn(&mut self.ids, &self.catalog, &mut *self.world.borrow().rng());
+        self.world.borrow_mut().set_enemies(enemies);
+        self.focus = None;
+        self.log.push(self.encounter.to_string());
+    }
+
+    fn check_outcome(&mut self) {
+        let Some(outcome) = encounter::outcome(&self.heroes_dyn(), &self.enemies_dyn()) else {
+            return;
+        };
+        self.close_orders();
+        self.log.push(match outcome {
+            Outcome::Victory => format!("Victory! {} cleared", self.encounter),
+            Outcome::Defeat => format!("Defeat... retreating to stage {}", self.encounter.checkpoint),
+        });
+        self.resolved = Some((outcome, Instant::now()));
+    }
 
+    fn next_battle(&mut self, outcome: Outcome) {
+        // paid out here and not when the wave falls, the pause before isn't saved
+        // and a reload during it would roll the same wave again
+        let loot = self
+            .encounter
+            .next_wave(outcome, &self.world, &mut self.ids, &self.catalog);
+        self.log.extend(loot.collect(&mut self.inventory.inventory, &self.catalog));
+        self.resolved = None;
+        self.focus = None;
+        self.log.push(self.encounter.to_string());
+        self.reset_turn();
         self.timer = Instant::now();
     }
 
+    fn reset_turn(&mut self) {
+        self.timeline.borrow_mut().sync();
+
+        self.current_turn = self.timeline.borrow().current();
+        self.heroes.set_turn(self.current_turn);
+        self.enemies.set_turn(self.current_turn);
+    }
+
+    fn snapshot(&self) -> SaveGame {
+        SaveGame {
+            version: SAVE_VERSION,
+            saved_at: SaveGame::now(),
+            heroes: self.world.borrow().heroes(),
+            enemies: self.world.borrow().enemies(),
+            timeline: self.timeline.borrow().gauges(),
+            current_turn: self.current_turn,
+            log: self.log.to_vec(),
+            timer_ms: self.timer.elapsed().as_millis() as u64,
+            ids: self.ids.clone(),
+            encounter: self.encounter.clone(),
+            inventory: self.inventory.inventory.clone(),
+            seed: Some(self.world.borrow().seed()),
+            rng_position: self.world.borrow().rng_position(),
+        }
+    }
+
+    fn restore(&mut self, save: SaveGame) {
+        self.close_orders();
+        self.world.borrow_mut().replace(save.heroes, save.enemies);
+        // saves from before seeds were kept carry on with a fresh one
+        match save.seed {
+            Some(seed) => self.world.borrow_mut().reseed(seed, save.rng_position),
+            None => self.world.borrow_mut().reseed(rand::random(), 0),
+        }
+        self.ids = save.ids;
+        self.encounter = save.encounter;
+        self.inventory.inventory = save.inventory;
+        self.resolved = None;
+        self.reset_turn();
+        self.timeline.borrow_mut().restore(save.timeline, save.current_turn);
+        self.current_turn = self.timeline.borrow().current();
+        self.heroes.set_turn(self.current_turn);
+        self.enemies.set_turn(self.current_turn);
+        self.log = Log::from(save.log);
+        self.timer = Instant::now()
+            .checked_sub(Duration::from_millis(save.timer_ms))
+            .unwrap_or_else(Instant::now);
+    }
+
+    // reopening the game catches up on the time it was closed, a manual load doesn't
+    fn resume(&mut self, slot: &str, save: SaveGame) {
+        let away = Duration::from_secs(SaveGame::now().saturating_sub(save.saved_at));
+        self.restore(save);
+        self.log.push(format!("Loaded slot '{}'", slot));
+
+        let summary = offline::simulate(
+            away,
+            self.battle_pause,
+            &mut self.timeline.borrow_mut(),
+            &mut self.encounter,
+            &mut self.ids,
+            &mut self.inventory.inventory,
+            &self.catalog,
+        );
+        if summary.turns > 0 {
+            self.log.push(summary.to_string());
+        }
+        self.current_turn = self.timeline.borrow().current();
+        self.heroes.set_turn(self.current_turn);
+        self.enemies.set_turn(self.current_turn);
+    }
+
+    fn save(&mut self, slot: &str) {
+        match self.saves.write(slot, &self.snapshot()) {
+            Ok(path) => self.log.push(format!("Saved to {}", path.display())),
+            Err(e) => self.log.push(format!("Save failed: {:#}", e)),
+        }
+    }
+
+    // a slot that didn't load might still be fixed by hand, autosaves go somewhere else
+    fn keep_unreadable(&mut self, failed: Unreadable) {
+        for (slot, e) in failed {
+            self.log.push(format!("Could not load slot '{}': {:#}", slot, e));
+            if slot == self.autosave {
+                self.autosave = format!("{}-{}", slot, SaveGame::now());
+                self.log.push(format!("Autosaving to '{}' instead", self.autosave));
+            }
+        }
+    }
+
+    fn autosave(&mut self) {
+        let slot = self.autosave.clone();
+        self.save(&slot);
+    }
+
+    fn load(&mut self, slot: &str) {
+        match self.saves.read(slot) {
+            Ok(save) => {
+                self.restore(save);
+                self.log.push(format!("Loaded slot '{}'", slot));
+            }
+            Err(e) => self.log.push(format!("Load failed: {:#}", e)),
+        }
+    }
+
+    fn run_command(&mut self, input: &str, ctx: &mut Context) -> Option<StateType> {
+        let result = self
+            .commands
+            .parse(input)
+            .map_err(anyhow::Error::from)
+            .and_then(|invocation| (invocation.handler)(self, &invocation.args, ctx));
+        match result {
+            Ok(state) => state,
+            Err(e) => {
+                self.log.push(format!("Error: {:#}", e));
+                None
+            }
+        }
+    }
+}
+
+impl State for InGame {
+    fn init(&mut self, ctx: &Context) {
+        self.catalog = ctx.catalog.clone();
+        self.inventory.set_catalog(ctx.catalog.clone());
+        self.manual_timeout = ctx.config.manual_timeout;
+        self.battle_pause = ctx.config.battle_pause;
+        self.command
+            .set_history(History::load(History::file(), ctx.config.history_len));
+        // a given seed always starts over, so the run can be replayed from the beginning
+        if let Some(seed) = ctx.config.seed {
+            self.autosave = save::seeded_slot(seed);
+            self.world.borrow_mut().reseed(seed, 0);
+            self.new_game();
+            return;
+        }
+        let (latest, failed) = self.saves.latest();
+        match latest {
+            Some((slot, save)) => self.resume(&slot, save),
+            None => {
+                self.world.borrow_mut().reseed(rand::random(), 0);
+                self.new_game();
+            }
+        }
+        // after resuming, which brings back the saved log
+        self.keep_unreadable(failed);
+    }
+
     fn update(&mut self, ctx: &mut Context) -> Option<StateType> {
-        self.turn.update_next_round_order();
-        if self.timer.elapsed() >= Duration::from_secs(2) {
-            self.timer = Instant::now();
-            self.current_turn = self.turn.pop();
-            self.heroes.set_turn(self.current_turn);
-            self.enemies.set_turn(self.current_turn);
-            ctx.push_log(format!("Current turn: {:?}\n", self.current_turn));
+        match self.resolved {
+            Some((outcome, at)) => {
+                if at.elapsed() >= self.battle_pause / self.speed {
+                    self.next_battle(outcome);
+                }
+                return None;
+            }
+            None => self.check_outcome(),
+        }
+        if self.resolved.is_some() {
+            return None;
+        }
+        if let Some(orders) = &self.orders {
+            if orders.since.elapsed() >= self.manual_timeout {
+                self.act_on_its_own();
+            }
+            return None;
+        }
+        // someone who died before their turn came up doesn't hold up the battle
+        if self
+            .current_turn
+            .is_some_and(|id| !self.is_alive(id))
+        {
+            self.advance();
+        }
+        if self.timer.elapsed() >= TURN_DELAY / self.speed {
+            let can_act = self
+                .current_hero()
+                .is_some_and(|h| h.borrow().status().can_act());
+            // don't pull the player out of a command they are typing
+            if self.manual && can_act {
+                if !self.command.is_entered() {
+                    self.open_orders();
+                }
+            } else {
+                self.step(self.focus);
+                ctx.push_log(format!("Current turn: {:?}\n", self.current_turn));
+            }
         }
         None
     }
 
     fn render(&mut self, frame: &mut Frame, area: Rect) {
-        let heroes_count = self.heroes.entities.len() as u16;
-        let enemies_count = self.enemies.entities.len() as u16;
+        let heroes_count = self.heroes.entities().len() as u16;
+        let enemies_count = self.enemies.entities().len() as u16;
         let entity_info_height = if heroes_count > enemies_count && heroes_count + 2 < 9 {
             heroes_count + 2
         } else if enemies_count + 2 < 9 {
@@ -173,12 +842,24 @@ impl State for InGame {
         let layout = Layout::new(
             Direction::Vertical,
             [
+                Constraint::Length(1),
                 Constraint::Length(entity_info_height),
                 Constraint::Min(1),
                 Constraint::Length(3),
             ],
         )
         .split(area);
+        let header = match self.resolved {
+            Some((Outcome::Victory, _)) => " Victory!",
+            Some((Outcome::Defeat, _)) => " Defeat, retreating",
+            None if self.manual => " Manual",
+            None => "",
+        };
+        frame.render_widget(
+            Paragraph::new(format!(" {}{}", self.encounter, header)).fg(PRIMARY).bold(),
+            layout[0],
+        );
+        let layout = &layout[1..];
         let entity_info_layout = Layout::new(
             Direction::Horizontal,
             [
@@ -206,19 +887,48 @@ impl State for InGame {
             entity_info_layout[2],
             self.selected_widget == StateWidget::Enemy,
         );
+        let log_layout = Layout::new(
+            Direction::Horizontal,
+            [Constraint::Ratio(2, 3), Constraint::Ratio(1, 3)],
+        )
+        .split(layout[1]);
         let log_color = if self.selected_widget == StateWidget::Log {
             ACCENT
         } else {
             PRIMARY
         };
-        frame.render_widget(
-            List::new(self.log.clone()).fg(log_color).block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .fg(log_color)
-                    .title(" Log "),
-            ),
-            layout[1],
+        // an entered list swaps the log for the details of whoever is selected
+        let inspected = match self.selected_widget {
+            StateWidget::Hero if self.heroes.is_entered() => {
+                self.heroes.selected().map(|e| e as SharedEntity)
+            }
+            StateWidget::Enemy if self.enemies.is_entered() => {
+                self.enemies.selected().map(|e| e as SharedEntity)
+            }
+            _ => None,
+        };
+        if inspected.is_some() {
+            self.details.set_entity(inspected);
+            self.details.render(" ", frame, log_layout[0], false);
+        } else {
+            // newest at the bottom, whatever doesn't fit scrolls off the top
+            let visible = log_layout[0].height.saturating_sub(2) as usize;
+            frame.render_widget(
+                List::new(self.log.tail(visible).cloned().collect::<Vec<_>>()).fg(log_color).block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .fg(log_color)
+                        .title(" Log "),
+                ),
+                log_layout[0],
+            );
+        }
+        self.inventory.set_hero(self.heroes.selected());
+        self.inventory.render(
+            " Inventory ",
+            frame,
+            log_layout[1],
+            self.selected_widget == StateWidget::Inventory,
         );
         self.command.render(
             " Command ",
@@ -226,6 +936,17 @@ impl State for InGame {
             layout[2],
             self.selected_widget == StateWidget::Command,
         );
+        if let (Some(orders), Some(hero)) = (&self.orders, self.current_hero()) {
+            if orders.choice.is_none() {
+                let left = self.manual_timeout.saturating_sub(orders.since.elapsed());
+                self.menu.render(
+                    &format!(" {} ({}s) ", hero.borrow().info().name, left.as_secs()),
+                    frame,
+                    layout[1],
+                    self.selected_widget == StateWidget::Menu,
+                );
+            }
+        }
     }
 
     fn handle_event(&mut self, event: Event, ctx: &mut Context) -> Option<StateType> {
@@ -235,16 +956,17 @@ impl State for InGame {
                 match k.code {
                     KeyCode::Char(c) => {
                         if self.selected_widget == StateWidget::Command {
-                            if k.modifiers == KeyModifiers::CONTROL {
-                                self.command.pop_word();
-                            } else {
+                            // control and alt chords are editing keys, handled by the component
+                            if !k.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
                                 self.command.push(c)
                             }
                         }
                         match c {
                             'q' if !self.command.is_entered() => {
+                                self.autosave();
                                 ctx.should_quit = true;
                             }
+                            'm' if !self.is_in_a_widget => self.set_manual(!self.manual),
                             ':' if !self.command.is_entered() => {
                                 self.selected_widget = StateWidget::Command;
                                 self.command.enter();
@@ -261,48 +983,51 @@ impl State for InGame {
                                 StateWidget::Turn => self.turn.enter(),
                                 StateWidget::Log => (),
                                 StateWidget::Command => self.command.enter(),
+                                StateWidget::Menu => self.menu.enter(),
+                                StateWidget::Inventory => self.inventory.enter(),
                             }
 
                             if self.selected_widget != StateWidget::Log {
                                 self.is_in_a_widget = true;
                             } 
-                        } else {
-                            match self.selected_widget {
-                                StateWidget::Command => {
-                                    if self.command.is_entered() {
-                                        if let Some(command) = self.command.execute() {
-                                            self.log.push(command);
-                                        }
-                                    }
-                                },
-                                _ => ()
+                        } else if self.selected_widget == StateWidget::Menu {
+                            self.pick_choice();
+                        } else if self.selected_widget == StateWidget::Inventory {
+                            self.equip_selected();
+                        } else if matches!(self.selected_widget, StateWidget::Hero | StateWidget::Enemy)
+                            && self.orders.as_ref().is_some_and(|o| o.choice.is_some())
+                        {
+                            self.pick_target();
+                        } else if self.selected_widget == StateWidget::Command
+                            && self.command.is_entered()
+                        {
+                            if let Some(command) = self.command.execute() {
+                                self.log.push(format!(":{}", command));
+                                if let Some(state) = self.run_command(&command, ctx) {
+                                    return Some(state);
+                                }
                             }
                         }
                     }
-                    KeyCode::Backspace => {
-                        if self.selected_widget == StateWidget::Command && self.command.is_entered()
-                        {
-                            self.command.pop()
-                        };
+                    KeyCode::Backspace
+                        if self.selected_widget == StateWidget::Command
+                            && self.command.is_entered() =>
+                    {
+                        self.command.pop()
                     }
-                    KeyCode::Delete => {
-                        //self.input.pop_word()
+                    KeyCode::Tab | KeyCode::BackTab
+                        if self.selected_widget == StateWidget::Command
+                            && self.command.is_entered() =>
+                    {
+                        self.complete(k.code == KeyCode::Tab)
                     }
-                    KeyCode::Tab if self.selected_widget == StateWidget::Command => {
-                        self.command.exit();
-                    }
-                    KeyCode::Esc => {
-                        if self.is_in_a_widget {
-                            match self.selected_widget {
-                                StateWidget::Hero => self.heroes.exit(),
-                                StateWidget::Enemy => self.enemies.exit(),
-                                StateWidget::Turn => self.turn.exit(),
-                                StateWidget::Log => (),
-                                StateWidget::Command => self.command.exit(),
-                            }
-                            self.is_in_a_widget = false;
-                        } 
+                    KeyCode::Esc if self.command.is_searching() => self.command.cancel_search(),
+                    KeyCode::Esc
+                        if self.orders.is_some() && self.selected_widget != StateWidget::Command =>
+                    {
+                        self.cancel_order()
                     }
+                    KeyCode::Esc if self.is_in_a_widget => self.exit_widget(),
                     _ => (),
                 }
                 ctx.push_log(format!("{:?}\n", k.code));
@@ -317,8 +1042,16 @@ impl State for InGame {
                 StateWidget::Turn => self.turn.handle_event(&event),
                 StateWidget::Log => (),
                 StateWidget::Command => self.command.handle_event(&event),
+                StateWidget::Menu => self.menu.handle_event(&event),
+                StateWidget::Inventory => self.inventory.handle_event(&event),
             }
         }
+        if self.command.is_entered() {
+            let hint = self
+                .commands
+                .hint(self.command.content(), &|kind, args| self.candidates(kind, args));
+            self.command.set_hint(hint);
+        }
         None
     }
 
@@ -326,5 +1059,64 @@ impl State for InGame {
         StateType::InGame
     }
 
-    fn destroy(&mut self) {}
+    fn destroy(&mut self) {
+        self.autosave();
+    }
+
+    fn seed(&self) -> Option<u64> {
+        Some(self.world.borrow().seed())
+    }
+}
+
+#[cfg(test)]
+use crate::core::data::{LootEntry, GOLD};
+
+#[test]
+fn test_loot_is_granted_once_across_a_reload() {
+    let mut catalog = Catalog::bundled();
+    for archetype in &mut catalog.enemies {
+        archetype.loot = vec![LootEntry { item: GOLD.to_owned(), weight: 1 }];
+    }
+    let mut game = InGame::new();
+    game.catalog = Rc::new(catalog);
+    game.world.borrow_mut().reseed(1, 0);
+    game.new_game();
+    for enemy in game.enemies_dyn() {
+        enemy.borrow_mut().stat_and_status_mut().1.set_alive(false);
+    }
+
+    game.check_outcome();
+    let save = game.snapshot();
+    game.restore(save);
+    game.check_outcome();
+    assert_eq!(game.inventory.inventory.gold, 0);
+
+    let Some((outcome, _)) = game.resolved else {
+        panic!("the wave should be over");
+    };
+    game.next_battle(outcome);
+    let gold = game.inventory.inventory.gold;
+    assert!(gold > 0);
+    assert_eq!(game.log.to_vec().iter().filter(|l| l.starts_with("Loot:")).count(), 1);
+    game.check_outcome();
+    assert_eq!(game.inventory.inventory.gold, gold);
+}
+
+#[test]
+fn test_unreadable_autosave_is_not_overwritten() {
+    let mut game = InGame::new();
+    game.keep_unreadable(vec![(AUTOSAVE_SLOT.to_owned(), anyhow!("slot 'auto' is corrupted"))]);
+    assert_ne!(game.autosave, AUTOSAVE_SLOT);
+    assert!(game.autosave.starts_with("auto-"));
+    assert!(game.log.to_vec()[0].contains("is corrupted"));
+}
+
+#[test]
+fn test_the_starting_party_can_revive() {
+    let mut game = InGame::new();
+    game.catalog = Rc::new(Catalog::bundled());
+    game.new_game();
+    let heroes = game.heroes_dyn();
+    assert_eq!(heroes.len(), 3);
+    assert!(heroes.iter().any(|h| h.borrow().skills().iter().any(|s| s.skill.revives())));
 }
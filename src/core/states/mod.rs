@@ -9,12 +9,16 @@ pub use ingame::InGame;
 use crate::{Context, Event};
 
 pub trait State {
-    fn init(&mut self);
+    fn init(&mut self, ctx: &Context);
     fn update(&mut self, ctx: &mut Context) -> Option<StateType>;
     fn render(&mut self, frame: &mut Frame, area: Rect);
     fn handle_event(&mut self, event: Event, ctx: &mut Context) -> Option<StateType>;
     fn get_type(&self) -> StateType;
     fn destroy(&mut self);
+    // the seed of the running game, for the debug pane
+    fn seed(&self) -> Option<u64> {
+        None
+    }
 }
 
 #[derive(Debug, PartialEq)]
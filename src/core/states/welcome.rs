@@ -1,26 +1,122 @@
+use anyhow::Result;
 #[cfg(not(target_arch = "wasm32"))]
-use crossterm::event::KeyCode;
+use crossterm::event::{KeyCode, KeyModifiers};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::Stylize,
+    text::Line,
     widgets::Paragraph,
     Frame,
 };
 
-use crate::{core::consts::PRIMARY, Context, Event};
+use crate::{
+    core::{
+        commands::{ArgKind, ArgSpec, Args, CommandSpec, Registry},
+        components::{Command, Component},
+        consts::PRIMARY,
+        history::History,
+        save::Saves,
+    },
+    Context, Event,
+};
 
 use super::{State, StateType};
 
-pub struct Welcome {}
+pub struct Welcome {
+    command: Command,
+    commands: Registry<Welcome>,
+    output: Vec<String>,
+}
 
 impl Welcome {
     pub fn new() -> Self {
-        Welcome {}
+        Welcome {
+            command: Command::new(),
+            commands: Self::commands(),
+            output: Vec::new(),
+        }
+    }
+
+    fn commands() -> Registry<Self> {
+        Registry::new()
+            .register(
+                CommandSpec::new("help", "list commands or describe one", Self::cmd_help)
+                    .arg(ArgSpec::optional("command", ArgKind::Command)),
+            )
+            .register(
+                CommandSpec::new("start", "start or continue the game", Self::cmd_start)
+                    .alias("play"),
+            )
+            .register(CommandSpec::new("saves", "list save slots", Self::cmd_saves))
+            .register(
+                CommandSpec::new("quit", "quit the game", Self::cmd_quit)
+                    .alias("q")
+                    .alias("exit"),
+            )
+    }
+
+    fn cmd_help(&mut self, args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        self.output = self.commands.help(args.text("command"))?;
+        Ok(None)
+    }
+
+    fn cmd_start(&mut self, _args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        Ok(Some(StateType::InGame))
+    }
+
+    fn cmd_saves(&mut self, _args: &Args, _ctx: &mut Context) -> Result<Option<StateType>> {
+        let slots = Saves::new().slots();
+        self.output = if slots.is_empty() {
+            vec!["No saves yet".to_owned()]
+        } else {
+            slots
+        };
+        Ok(None)
+    }
+
+    fn cmd_quit(&mut self, _args: &Args, ctx: &mut Context) -> Result<Option<StateType>> {
+        ctx.should_quit = true;
+        Ok(None)
+    }
+
+    fn complete(&mut self, forward: bool) {
+        if self.command.cycle_completion(forward) {
+            return;
+        }
+        let completion = self
+            .commands
+            .complete(self.command.before_cursor(), &Self::candidates);
+        self.command.complete(completion);
+    }
+
+    fn candidates(kind: ArgKind, _args: &[String]) -> Vec<String> {
+        match kind {
+            ArgKind::Slot => Saves::new().slots(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn run_command(&mut self, input: &str, ctx: &mut Context) -> Option<StateType> {
+        let result = self
+            .commands
+            .parse(input)
+            .map_err(anyhow::Error::from)
+            .and_then(|invocation| (invocation.handler)(self, &invocation.args, ctx));
+        match result {
+            Ok(state) => state,
+            Err(e) => {
+                self.output = vec![format!("Error: {:#}", e)];
+                None
+            }
+        }
     }
 }
 
 impl State for Welcome {
-    fn init(&mut self) {}
+    fn init(&mut self, ctx: &Context) {
+        self.command
+            .set_history(History::load(History::file(), ctx.config.history_len));
+    }
 
     fn update(&mut self, _ctx: &mut Context) -> Option<StateType> {
         None
@@ -29,16 +125,22 @@ impl State for Welcome {
     fn render(&mut self, frame: &mut Frame, area: Rect) {
         let main_layout = Layout::new(
             Direction::Vertical,
-            [Constraint::Min(1), Constraint::Max(1)],
+            [
+                Constraint::Min(1),
+                Constraint::Max(if self.command.is_entered() { 3 } else { 1 }),
+            ],
         )
         .split(area);
-        let padding_top = ((area.height - 1) as f32 / 2.).floor() as u16;
-        let padding_bot = ((area.height - 1) as f32 / 2.).ceil() as u16;
+        let output_height = self.output.len() as u16;
+        let free = main_layout[0].height.saturating_sub(1 + output_height);
+        let padding_top = (free as f32 / 2.).floor() as u16;
+        let padding_bot = (free as f32 / 2.).ceil() as u16;
         let layout = Layout::new(
             Direction::Vertical,
             [
                 Constraint::Length(padding_top),
                 Constraint::Length(1),
+                Constraint::Length(output_height),
                 Constraint::Length(padding_bot),
             ],
         )
@@ -53,7 +155,22 @@ impl State for Welcome {
             layout[1],
         );
         frame.render_widget(
-            Paragraph::new("Press 'q' to close")
+            Paragraph::new(
+                self.output
+                    .iter()
+                    .map(|l| Line::from(l.as_str()))
+                    .collect::<Vec<_>>(),
+            )
+            .alignment(Alignment::Center)
+            .fg(PRIMARY),
+            layout[2],
+        );
+        if self.command.is_entered() {
+            self.command.render(" Command ", frame, main_layout[1], true);
+            return;
+        }
+        frame.render_widget(
+            Paragraph::new("Press 'q' to close, ':' for commands")
                 .alignment(Alignment::Left)
                 .fg(PRIMARY),
             main_layout[1],
@@ -71,6 +188,29 @@ impl State for Welcome {
             #[cfg(not(target_arch = "wasm32"))]
             Event::Key(key) => {
                 ctx.push_log(format!("{:?}\n", key.code));
+                if self.command.is_entered() {
+                    match key.code {
+                        KeyCode::Char(c)
+                            if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                        {
+                            self.command.push(c)
+                        }
+                        KeyCode::Backspace => self.command.pop(),
+                        KeyCode::Esc if self.command.is_searching() => self.command.cancel_search(),
+                        KeyCode::Esc => self.command.exit(),
+                        KeyCode::Tab | KeyCode::BackTab => self.complete(key.code == KeyCode::Tab),
+                        KeyCode::Enter => {
+                            if let Some(command) = self.command.execute() {
+                                self.command.exit();
+                                return self.run_command(&command, ctx);
+                            }
+                        }
+                        _ => self.command.handle_event(&Event::Key(key)),
+                    }
+                    let hint = self.commands.hint(self.command.content(), &Self::candidates);
+                    self.command.set_hint(hint);
+                    return None;
+                }
                 match key.code {
                     KeyCode::Enter => {
                         return Some(StateType::InGame);
@@ -78,6 +218,7 @@ impl State for Welcome {
                     KeyCode::Char('q') => {
                         ctx.should_quit = true;
                     }
+                    KeyCode::Char(':') => self.command.enter(),
                     _ => (),
                 }
             }
@@ -0,0 +1,274 @@
+use std::{cell::RefCell, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+use super::{entities::Id, world::SharedWorld};
+
+// everyone runs the same track, whoever reaches the end first acts and starts over
+pub const TRACK: f64 = 10000.;
+
+pub type SharedTimeline = Rc<RefCell<Timeline>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Gauge {
+    pub id: Id,
+    // left to run before acting
+    pub distance: f64,
+}
+
+// who acts when, shared by the game, the offline catch up and the simulator.
+// the Turn pane only draws it
+#[derive(Debug)]
+pub struct Timeline {
+    world: SharedWorld,
+    gauges: Vec<Gauge>,
+    current: Option<Id>,
+}
+
+impl Timeline {
+    pub fn new(world: SharedWorld) -> Self {
+        Self {
+            world,
+            gauges: Vec::new(),
+            current: None,
+        }
+    }
+
+    pub fn shared(world: SharedWorld) -> SharedTimeline {
+        Rc::new(RefCell::new(Self::new(world)))
+    }
+
+    pub fn world(&self) -> &SharedWorld {
+        &self.world
+    }
+
+    // picks up who is in the world now, known entities keep their progress,
+    // newcomers like summons start at the back of the track
+    pub fn sync(&mut self) {
+        let gauges = self
+            .world
+            .borrow()
+            .ids()
+            .into_iter()
+            .map(|id| {
+                self.gauges
+                    .iter()
+                    .find(|g| g.id == id)
+                    .cloned()
+                    .unwrap_or(Gauge { id, distance: TRACK })
+            })
+            .collect();
+        self.gauges = gauges;
+        if !self.current.is_some_and(|id| self.is_alive(id)) {
+            self.current = None;
+            self.advance();
+        }
+    }
+
+    pub fn gauges(&self) -> Vec<Gauge> {
+        self.gauges.clone()
+    }
+
+    // unknown ids are dropped so render can't trip on them
+    pub fn restore(&mut self, gauges: Vec<Gauge>, current: Option<Id>) {
+        for gauge in &mut self.gauges {
+            if let Some(saved) = gauges.iter().find(|g| g.id == gauge.id) {
+                gauge.distance = saved.distance.clamp(0., TRACK);
+            }
+        }
+        self.current = None;
+        match current.filter(|id| self.is_alive(*id)) {
+            Some(id) => {
+                self.current = Some(id);
+                if let Some(gauge) = self.gauges.iter_mut().find(|g| g.id == id) {
+                    gauge.distance = 0.;
+                }
+            }
+            None => self.advance(),
+        }
+    }
+
+    pub fn current(&self) -> Option<Id> {
+        self.current
+    }
+
+    // the current actor goes back to the start of the track and the next one comes up
+    pub fn pop(&mut self) -> Option<Id> {
+        if let Some(id) = self.current.take() {
+            if let Some(gauge) = self.gauges.iter_mut().find(|g| g.id == id) {
+                gauge.distance = TRACK;
+            }
+        }
+        self.advance();
+        self.current
+    }
+
+    fn is_alive(&self, id: Id) -> bool {
+        self.world.borrow().is_alive(id)
+    }
+
+    // the living, with their current speed, read fresh so buffs and debuffs apply right away
+    fn runners(&self) -> Vec<(Id, f64, f64)> {
+        let world = self.world.borrow();
+        self.gauges
+            .iter()
+            .filter_map(|g| {
+                let entity = world.get(g.id)?;
+                let entity = entity.borrow();
+                entity
+                    .status()
+                    .is_alive()
+                    .then(|| (g.id, g.distance, entity.effective_stat().spd.max(1) as f64))
+            })
+            .collect()
+    }
+
+    // who reaches the end first and how long it takes, faster ones win ties
+    fn next(runners: &[(Id, f64, f64)]) -> Option<(usize, f64)> {
+        runners
+            .iter()
+            .enumerate()
+            .map(|(i, (_, distance, spd))| (i, distance / spd, *spd))
+            .min_by(|a, b| a.1.total_cmp(&b.1).then(b.2.total_cmp(&a.2)))
+            .map(|(i, time, _)| (i, time))
+    }
+
+    fn advance(&mut self) {
+        // the dead don't run, if they get revived they start over from the back
+        let world = self.world.borrow();
+        for gauge in &mut self.gauges {
+            if !world.is_alive(gauge.id) {
+                gauge.distance = TRACK;
+            }
+        }
+        let runners = self.runners();
+        let Some((winner, time)) = Self::next(&runners) else {
+            return;
+        };
+        for (id, _, spd) in &runners {
+            if let Some(gauge) = self.gauges.iter_mut().find(|g| g.id == *id) {
+                gauge.distance = (gauge.distance - spd * time).max(0.);
+            }
+        }
+        let id = runners[winner].0;
+        if let Some(gauge) = self.gauges.iter_mut().find(|g| g.id == id) {
+            gauge.distance = 0.;
+        }
+        self.current = Some(id);
+    }
+
+    // the next n actions and the action value between each one and the one before
+    pub fn projection(&self, n: usize) -> Vec<(Id, u32)> {
+        let mut runners = self.runners();
+        let mut actions = Vec::new();
+        if let Some(current) = self.current.filter(|id| runners.iter().any(|r| r.0 == *id)) {
+            actions.push((current, 0));
+        }
+        while actions.len() < n {
+            if let Some((last, _)) = actions.last() {
+                if let Some(runner) = runners.iter_mut().find(|r| r.0 == *last) {
+                    runner.1 = TRACK;
+                }
+            }
+            let Some((winner, time)) = Self::next(&runners) else {
+                break;
+            };
+            for runner in &mut runners {
+                runner.1 = (runner.1 - runner.2 * time).max(0.);
+            }
+            actions.push((runners[winner].0, time.round() as u32));
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+use super::{
+    entities::{enemy::Enemy, hero::Hero, Entity, SharedEntity},
+    types::effects::Slow,
+    world::World,
+};
+
+#[cfg(test)]
+fn test_timeline(speeds: &[i32]) -> (Vec<Id>, Vec<SharedEntity>, Timeline) {
+    let world = World::shared();
+    for spd in speeds {
+        let mut hero = Hero::new();
+        hero.stat.spd = *spd;
+        world.borrow_mut().add_hero(hero);
+    }
+    let entities = world.borrow().heroes_dyn();
+    let ids = world.borrow().ids();
+    let mut timeline = Timeline::new(world);
+    timeline.sync();
+    (ids, entities, timeline)
+}
+
+#[test]
+fn test_faster_entities_act_more_often() {
+    let (ids, _, mut timeline) = test_timeline(&[10, 20]);
+    assert_eq!(timeline.current(), Some(ids[1]));
+    let order: Vec<Id> = (0..6).filter_map(|_| timeline.pop()).collect();
+    assert_eq!(order.iter().filter(|id| **id == ids[1]).count(), 4);
+    assert_eq!(order.iter().filter(|id| **id == ids[0]).count(), 2);
+
+    let projection = timeline.projection(3);
+    assert_eq!(projection[0], (timeline.current().unwrap(), 0));
+    assert!(projection[1..].iter().all(|(_, gap)| *gap <= 500));
+}
+
+#[test]
+fn test_speed_changes_and_deaths_reorder_live() {
+    let (ids, entities, mut timeline) = test_timeline(&[10, 10]);
+    let count = |timeline: &mut Timeline, id: Id| {
+        (0..30).filter_map(|_| timeline.pop()).filter(|i| *i == id).count()
+    };
+    assert_eq!(count(&mut timeline, ids[0]), 15);
+    // slowed to half speed, it acts half as often as the other one
+    entities[1].borrow_mut().apply_effect(Box::new(Slow { spd: 5, duration: i32::MAX }));
+    assert_eq!(count(&mut timeline, ids[0]), 20);
+
+    entities[1].borrow_mut().stat_and_status_mut().1.set_alive(false);
+    assert!(timeline.projection(4).iter().all(|(id, _)| *id == ids[0]));
+    assert_eq!(count(&mut timeline, ids[0]), 30);
+}
+
+#[test]
+fn test_summons_join_at_the_back_and_saves_restore() {
+    let (_, _, mut timeline) = test_timeline(&[10, 14]);
+    timeline.pop();
+    let current = timeline.current();
+
+    let summon = Enemy::new();
+    let summon_id = summon.id();
+    timeline.world.borrow_mut().add_enemy(summon);
+    timeline.sync();
+    assert_eq!(timeline.current(), current);
+    assert!(timeline.gauges().iter().any(|g| g.id == summon_id && g.distance == TRACK));
+
+    let mut restored = Timeline::new(timeline.world.clone());
+    restored.sync();
+    restored.restore(timeline.gauges(), current);
+    assert_eq!(restored.current(), current);
+    assert_eq!(restored.projection(12), timeline.projection(12));
+}
+
+#[test]
+fn test_empty_and_wiped_out_timelines() {
+    let mut timeline = Timeline::new(World::shared());
+    assert_eq!(timeline.pop(), None);
+    assert!(timeline.projection(12).is_empty());
+
+    let (ids, entities, mut timeline) = test_timeline(&[10, 10]);
+    for entity in &entities {
+        entity.borrow_mut().stat_and_status_mut().1.set_alive(false);
+    }
+    assert_eq!(timeline.pop(), None);
+    assert_eq!(timeline.current(), None);
+    assert!(timeline.projection(12).is_empty());
+
+    // the revived start from the back of the track
+    entities[1].borrow_mut().stat_and_status_mut().1.revive();
+    assert_eq!(timeline.pop(), Some(ids[1]));
+    assert_eq!(timeline.gauges()[0].distance, TRACK);
+}
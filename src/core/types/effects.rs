@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+
+use super::{status::Effect, BasicStat, Element};
+
+fn hurt(stat: &mut BasicStat, damage: i32) -> u32 {
+    let damage = (damage.max(0) as u32).min(stat.curr_hp);
+    stat.curr_hp -= damage;
+    damage
+}
+
+fn heal(stat: &mut BasicStat, amount: i32) -> u32 {
+    let amount = (amount.max(0) as u32).min(stat.max_hp.saturating_sub(stat.curr_hp));
+    stat.curr_hp += amount;
+    amount
+}
+
+// poison bites at the start of the turn, before the poisoned one can act
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DragonPoison {
+    pub damage: i32,
+    pub duration: i32,
+}
+
+#[typetag::serde]
+impl Effect for DragonPoison {
+    fn name(&self) -> &str {
+        "Poison"
+    }
+
+    fn element(&self) -> Option<Element> {
+        Some(Element::Poison)
+    }
+
+    fn duration(&self) -> i32 {
+        self.duration
+    }
+
+    fn set_duration(&mut self, duration: i32) {
+        self.duration = duration;
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+
+    fn on_apply(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        Some("is poisoned".to_owned())
+    }
+
+    fn on_turn_start(&mut self, stat: &mut BasicStat) -> Option<String> {
+        Some(format!("takes {} poison damage", hurt(stat, self.damage)))
+    }
+
+    fn on_expire(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        Some("is no longer poisoned".to_owned())
+    }
+}
+
+// burn lands after acting and also softens physical defense while it lasts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Burn {
+    pub damage: i32,
+    pub duration: i32,
+}
+
+#[typetag::serde]
+impl Effect for Burn {
+    fn name(&self) -> &str {
+        "Burn"
+    }
+
+    fn element(&self) -> Option<Element> {
+        Some(Element::Fire)
+    }
+
+    fn duration(&self) -> i32 {
+        self.duration
+    }
+
+    fn set_duration(&mut self, duration: i32) {
+        self.duration = duration;
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+
+    fn on_apply(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        Some("catches fire".to_owned())
+    }
+
+    fn on_turn_end(&mut self, stat: &mut BasicStat) -> Option<String> {
+        Some(format!("takes {} burn damage", hurt(stat, self.damage)))
+    }
+
+    fn on_expire(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        Some("stops burning".to_owned())
+    }
+
+    fn modify_stat(&self, stat: &mut BasicStat) {
+        stat.p_def -= stat.p_def / 4;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stun {
+    pub duration: i32,
+}
+
+#[typetag::serde]
+impl Effect for Stun {
+    fn name(&self) -> &str {
+        "Stun"
+    }
+
+    fn duration(&self) -> i32 {
+        self.duration
+    }
+
+    fn set_duration(&mut self, duration: i32) {
+        self.duration = duration;
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+
+    fn on_apply(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        Some("is stunned".to_owned())
+    }
+
+    fn on_expire(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        Some("is no longer stunned".to_owned())
+    }
+
+    fn prevents_action(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Regen {
+    pub heal: i32,
+    pub duration: i32,
+}
+
+#[typetag::serde]
+impl Effect for Regen {
+    fn name(&self) -> &str {
+        "Regen"
+    }
+
+    fn duration(&self) -> i32 {
+        self.duration
+    }
+
+    fn set_duration(&mut self, duration: i32) {
+        self.duration = duration;
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+
+    fn on_turn_start(&mut self, stat: &mut BasicStat) -> Option<String> {
+        Some(format!("regenerates {} hp", heal(stat, self.heal)))
+    }
+}
+
+// the shield breaks early once it has soaked up its whole amount
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shield {
+    pub amount: u32,
+    pub duration: i32,
+}
+
+#[typetag::serde]
+impl Effect for Shield {
+    fn name(&self) -> &str {
+        "Shield"
+    }
+
+    fn duration(&self) -> i32 {
+        self.duration
+    }
+
+    fn set_duration(&mut self, duration: i32) {
+        self.duration = duration;
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+
+    fn on_apply(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        Some(format!("is shielded for {}", self.amount))
+    }
+
+    fn on_expire(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        Some("loses the shield".to_owned())
+    }
+
+    fn absorb(&mut self, damage: u32) -> u32 {
+        let absorbed = damage.min(self.amount);
+        self.amount -= absorbed;
+        if self.amount == 0 {
+            self.duration = 0;
+        }
+        damage - absorbed
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Haste {
+    pub spd: i32,
+    pub duration: i32,
+}
+
+#[typetag::serde]
+impl Effect for Haste {
+    fn name(&self) -> &str {
+        "Haste"
+    }
+
+    fn duration(&self) -> i32 {
+        self.duration
+    }
+
+    fn set_duration(&mut self, duration: i32) {
+        self.duration = duration;
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+
+    fn on_apply(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        Some("speeds up".to_owned())
+    }
+
+    fn modify_stat(&self, stat: &mut BasicStat) {
+        stat.spd += self.spd;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Slow {
+    pub spd: i32,
+    pub duration: i32,
+}
+
+#[typetag::serde]
+impl Effect for Slow {
+    fn name(&self) -> &str {
+        "Slow"
+    }
+
+    fn duration(&self) -> i32 {
+        self.duration
+    }
+
+    fn set_duration(&mut self, duration: i32) {
+        self.duration = duration;
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Effect> {
+        Box::new(self.clone())
+    }
+
+    fn on_apply(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        Some("slows down".to_owned())
+    }
+
+    fn modify_stat(&self, stat: &mut BasicStat) {
+        stat.spd -= self.spd;
+    }
+}
+
+#[cfg(test)]
+use super::Status;
+
+#[test]
+fn test_poison_ticks_and_expires() {
+    let mut stat = BasicStat::default();
+    let mut status = Status::default();
+    status.apply_effect(Box::new(DragonPoison { damage: 4, duration: 2 }), &mut stat);
+
+    assert_eq!(status.turn_start(&mut stat), vec!["takes 4 poison damage"]);
+    assert_eq!(stat.curr_hp, 11);
+    status.turn_end(&mut stat);
+    status.turn_start(&mut stat);
+    assert_eq!(stat.curr_hp, 7);
+    assert_eq!(status.turn_end(&mut stat), vec!["is no longer poisoned"]);
+    assert!(status.effects().is_empty());
+    status.turn_start(&mut stat);
+    assert_eq!(stat.curr_hp, 7);
+}
+
+#[test]
+fn test_poison_can_kill() {
+    let mut stat = BasicStat::default();
+    let mut status = Status::default();
+    status.apply_effect(Box::new(DragonPoison { damage: 20, duration: 2 }), &mut stat);
+    status.turn_start(&mut stat);
+    assert_eq!(stat.curr_hp, 0);
+    assert!(!status.is_alive());
+}
+
+#[test]
+fn test_burn_ticks_on_turn_end_and_lowers_defense() {
+    let mut stat = BasicStat::default();
+    let mut status = Status::default();
+    status.apply_effect(Box::new(Burn { damage: 3, duration: 1 }), &mut stat);
+    assert_eq!(status.modified(&stat).p_def, 12);
+
+    assert!(status.turn_start(&mut stat).is_empty());
+    assert_eq!(stat.curr_hp, 15);
+    status.turn_end(&mut stat);
+    assert_eq!(stat.curr_hp, 12);
+    assert_eq!(status.modified(&stat).p_def, 15);
+}
+
+#[test]
+fn test_stun_prevents_action_for_its_duration() {
+    let mut stat = BasicStat::default();
+    let mut status = Status::default();
+    status.apply_effect(Box::new(Stun { duration: 1 }), &mut stat);
+    assert!(!status.can_act());
+    status.turn_end(&mut stat);
+    assert!(status.can_act());
+}
+
+#[test]
+fn test_regen_heals_up_to_max_hp() {
+    let mut stat = BasicStat {
+        curr_hp: 10,
+        ..Default::default()
+    };
+    let mut status = Status::default();
+    status.apply_effect(Box::new(Regen { heal: 3, duration: 3 }), &mut stat);
+    status.turn_start(&mut stat);
+    assert_eq!(stat.curr_hp, 13);
+    status.turn_start(&mut stat);
+    assert_eq!(stat.curr_hp, 15);
+}
+
+#[test]
+fn test_shield_absorbs_then_breaks() {
+    let mut stat = BasicStat::default();
+    let mut status = Status::default();
+    status.apply_effect(Box::new(Shield { amount: 5, duration: 3 }), &mut stat);
+    assert_eq!(status.absorb(3), 0);
+    assert_eq!(status.absorb(4), 2);
+    assert_eq!(status.absorb(4), 4);
+    status.turn_end(&mut stat);
+    assert!(status.effects().is_empty());
+}
+
+#[test]
+fn test_haste_and_slow_modify_speed() {
+    let mut stat = BasicStat::default();
+    let mut status = Status::default();
+    status.apply_effect(Box::new(Haste { spd: 10, duration: 2 }), &mut stat);
+    assert_eq!(status.modified(&stat).spd, 25);
+    status.apply_effect(Box::new(Slow { spd: 4, duration: 1 }), &mut stat);
+    assert_eq!(status.modified(&stat).spd, 21);
+    status.turn_end(&mut stat);
+    assert_eq!(status.modified(&stat).spd, 25);
+    assert_eq!(stat.spd, 15);
+}
+
+#[test]
+fn test_reapplying_refreshes_instead_of_stacking() {
+    let mut stat = BasicStat::default();
+    let mut status = Status::default();
+    status.apply_effect(Box::new(Haste { spd: 10, duration: 1 }), &mut stat);
+    status.apply_effect(Box::new(Haste { spd: 10, duration: 3 }), &mut stat);
+    assert_eq!(status.effects().len(), 1);
+    assert_eq!(status.effects()[0].duration(), 3);
+    assert_eq!(status.modified(&stat).spd, 25);
+}
+
+#[test]
+fn test_effects_roundtrip_through_json() {
+    let mut stat = BasicStat::default();
+    let mut status = Status::default();
+    status.apply_effect(Box::new(DragonPoison { damage: 2, duration: 2 }), &mut stat);
+    status.apply_effect(Box::new(Shield { amount: 5, duration: 2 }), &mut stat);
+    let json = serde_json::to_string(&status).unwrap();
+    let status: Status = serde_json::from_str(&json).unwrap();
+    assert_eq!(status.effects().len(), 2);
+    assert_eq!(status.effects()[1].name(), "Shield");
+}
@@ -0,0 +1,114 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Element {
+    Fire,
+    Ice,
+    Poison,
+    Holy,
+    Dark,
+}
+
+impl Element {
+    pub const ALL: [Element; 5] = [
+        Element::Fire,
+        Element::Ice,
+        Element::Poison,
+        Element::Holy,
+        Element::Dark,
+    ];
+}
+
+impl Display for Element {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Element::Fire => "fire",
+                Element::Ice => "ice",
+                Element::Poison => "poison",
+                Element::Holy => "holy",
+                Element::Dark => "dark",
+            }
+        )
+    }
+}
+
+// percent of the element's damage that is shrugged off, below 0 is a weakness, 100 is immunity
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Resistances {
+    pub fire: i32,
+    pub ice: i32,
+    pub poison: i32,
+    pub holy: i32,
+    pub dark: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Affinity {
+    Weak,
+    Resists,
+    Immune,
+}
+
+impl Resistances {
+    pub fn get(&self, element: Element) -> i32 {
+        match element {
+            Element::Fire => self.fire,
+            Element::Ice => self.ice,
+            Element::Poison => self.poison,
+            Element::Holy => self.holy,
+            Element::Dark => self.dark,
+        }
+    }
+
+    pub fn affinity(&self, element: Element) -> Option<Affinity> {
+        match self.get(element) {
+            r if r >= 100 => Some(Affinity::Immune),
+            r if r > 0 => Some(Affinity::Resists),
+            r if r < 0 => Some(Affinity::Weak),
+            _ => None,
+        }
+    }
+
+    pub fn is_immune(&self, element: Element) -> bool {
+        self.affinity(element) == Some(Affinity::Immune)
+    }
+
+    // what is left of the damage, weaknesses make it bigger
+    pub fn scale(&self, damage: u32, element: Element) -> u32 {
+        (damage as i64 * (100 - self.get(element)).max(0) as i64 / 100) as u32
+    }
+
+    // "weak to fire", "resists ice", for the log and the details panel
+    pub fn describe(&self, element: Element) -> Option<String> {
+        self.affinity(element).map(|affinity| match affinity {
+            Affinity::Weak => format!("weak to {}", element),
+            Affinity::Resists => format!("resists {}", element),
+            Affinity::Immune => format!("immune to {}", element),
+        })
+    }
+}
+
+#[test]
+fn test_resistances_scale_damage() {
+    let resistances = Resistances {
+        fire: 100,
+        ice: -50,
+        holy: 25,
+        ..Default::default()
+    };
+    assert_eq!(resistances.scale(20, Element::Fire), 0);
+    assert_eq!(resistances.scale(20, Element::Ice), 30);
+    assert_eq!(resistances.scale(20, Element::Holy), 15);
+    assert_eq!(resistances.scale(20, Element::Dark), 20);
+    assert!(resistances.is_immune(Element::Fire));
+    assert_eq!(resistances.describe(Element::Ice).as_deref(), Some("weak to ice"));
+    assert_eq!(resistances.describe(Element::Holy).as_deref(), Some("resists holy"));
+    assert_eq!(resistances.describe(Element::Poison), None);
+}
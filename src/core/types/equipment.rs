@@ -0,0 +1,233 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use super::{BasicStat, Element};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Slot {
+    Weapon,
+    Armor,
+    Accessory,
+}
+
+impl Slot {
+    pub const ALL: [Slot; 3] = [Slot::Weapon, Slot::Armor, Slot::Accessory];
+}
+
+impl Display for Slot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Slot::Weapon => "weapon",
+                Slot::Armor => "armor",
+                Slot::Accessory => "accessory",
+            }
+        )
+    }
+}
+
+// hp and mp pools are left out, damage and healing only ever look at the base ones
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StatKind {
+    PAtt,
+    MAtt,
+    PDef,
+    MDef,
+    Spd,
+    CritChance,
+    CritDamage,
+    Accuracy,
+    Evasion,
+}
+
+impl StatKind {
+    pub const ALL: [StatKind; 9] = [
+        StatKind::PAtt,
+        StatKind::MAtt,
+        StatKind::PDef,
+        StatKind::MDef,
+        StatKind::Spd,
+        StatKind::CritChance,
+        StatKind::CritDamage,
+        StatKind::Accuracy,
+        StatKind::Evasion,
+    ];
+
+    // the percentages, shown on their own line
+    pub fn is_secondary(&self) -> bool {
+        matches!(
+            self,
+            StatKind::CritChance | StatKind::CritDamage | StatKind::Accuracy | StatKind::Evasion
+        )
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatKind::PAtt => "p_att",
+            StatKind::MAtt => "m_att",
+            StatKind::PDef => "p_def",
+            StatKind::MDef => "m_def",
+            StatKind::Spd => "spd",
+            StatKind::CritChance => "crit_chance",
+            StatKind::CritDamage => "crit_damage",
+            StatKind::Accuracy => "accuracy",
+            StatKind::Evasion => "evasion",
+        }
+    }
+
+    pub fn get(&self, stat: &BasicStat) -> i32 {
+        match self {
+            StatKind::PAtt => stat.p_att,
+            StatKind::MAtt => stat.m_att,
+            StatKind::PDef => stat.p_def,
+            StatKind::MDef => stat.m_def,
+            StatKind::Spd => stat.spd,
+            StatKind::CritChance => stat.crit_chance,
+            StatKind::CritDamage => stat.crit_damage,
+            StatKind::Accuracy => stat.accuracy,
+            StatKind::Evasion => stat.evasion,
+        }
+    }
+
+    fn get_mut<'a>(&self, stat: &'a mut BasicStat) -> &'a mut i32 {
+        match self {
+            StatKind::PAtt => &mut stat.p_att,
+            StatKind::MAtt => &mut stat.m_att,
+            StatKind::PDef => &mut stat.p_def,
+            StatKind::MDef => &mut stat.m_def,
+            StatKind::Spd => &mut stat.spd,
+            StatKind::CritChance => &mut stat.crit_chance,
+            StatKind::CritDamage => &mut stat.crit_damage,
+            StatKind::Accuracy => &mut stat.accuracy,
+            StatKind::Evasion => &mut stat.evasion,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Modifier {
+    pub stat: StatKind,
+    #[serde(default)]
+    pub flat: i32,
+    // of the base stat
+    #[serde(default)]
+    pub percent: i32,
+}
+
+// what an equippable item does, kept whole on the hero so saves don't depend on the catalog
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Gear {
+    pub item: String,
+    pub name: String,
+    pub slot: Slot,
+    pub modifiers: Vec<Modifier>,
+    // weapons only, basic attacks deal this element
+    #[serde(default)]
+    pub element: Option<Element>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Equipment {
+    pub weapon: Option<Gear>,
+    pub armor: Option<Gear>,
+    pub accessory: Option<Gear>,
+}
+
+impl Equipment {
+    pub fn get(&self, slot: Slot) -> Option<&Gear> {
+        match slot {
+            Slot::Weapon => self.weapon.as_ref(),
+            Slot::Armor => self.armor.as_ref(),
+            Slot::Accessory => self.accessory.as_ref(),
+        }
+    }
+
+    fn slot_mut(&mut self, slot: Slot) -> &mut Option<Gear> {
+        match slot {
+            Slot::Weapon => &mut self.weapon,
+            Slot::Armor => &mut self.armor,
+            Slot::Accessory => &mut self.accessory,
+        }
+    }
+
+    // returns whatever was in the slot before
+    pub fn equip(&mut self, gear: Gear) -> Option<Gear> {
+        self.slot_mut(gear.slot).replace(gear)
+    }
+
+    pub fn unequip(&mut self, slot: Slot) -> Option<Gear> {
+        self.slot_mut(slot).take()
+    }
+
+    pub fn gear(&self) -> impl Iterator<Item = &Gear> {
+        Slot::ALL.into_iter().filter_map(|slot| self.get(slot))
+    }
+
+    // flat bonuses and percentages both scale off the base, so the order items are worn in doesn't matter
+    pub fn modified(&self, stat: &BasicStat) -> BasicStat {
+        let mut modified = stat.clone();
+        for modifier in self.gear().flat_map(|g| g.modifiers.iter()) {
+            let base = modifier.stat.get(stat);
+            *modifier.stat.get_mut(&mut modified) += modifier.flat + base * modifier.percent / 100;
+        }
+        modified
+    }
+
+    // the same stats with one slot swapped, for previews
+    pub fn with(&self, gear: Gear) -> Equipment {
+        let mut equipment = self.clone();
+        equipment.equip(gear);
+        equipment
+    }
+}
+
+#[cfg(test)]
+fn test_gear(slot: Slot, modifiers: Vec<Modifier>) -> Gear {
+    Gear {
+        item: slot.to_string(),
+        name: slot.to_string(),
+        slot,
+        modifiers,
+        element: None,
+    }
+}
+
+#[test]
+fn test_equipment_modifies_stats() {
+    let base = BasicStat {
+        p_att: 20,
+        spd: 10,
+        ..Default::default()
+    };
+    let mut equipment = Equipment::default();
+    let sword = test_gear(
+        Slot::Weapon,
+        vec![Modifier { stat: StatKind::PAtt, flat: 5, percent: 0 }],
+    );
+    assert_eq!(equipment.equip(sword.clone()), None);
+    let ring = test_gear(
+        Slot::Accessory,
+        vec![
+            Modifier { stat: StatKind::PAtt, flat: 0, percent: 50 },
+            Modifier { stat: StatKind::Spd, flat: -2, percent: 0 },
+        ],
+    );
+    equipment.equip(ring);
+    let stat = equipment.modified(&base);
+    assert_eq!((stat.p_att, stat.spd, stat.p_def), (35, 8, base.p_def));
+
+    let axe = test_gear(
+        Slot::Weapon,
+        vec![Modifier { stat: StatKind::PAtt, flat: 9, percent: 0 }],
+    );
+    assert_eq!(equipment.with(axe.clone()).modified(&base).p_att, 39);
+    assert_eq!(equipment.equip(axe), Some(sword));
+    assert!(equipment.unequip(Slot::Accessory).is_some());
+    assert_eq!(equipment.modified(&base).p_att, 29);
+}
@@ -6,3 +6,19 @@ pub use info::Info;
 
 pub mod status;
 pub use status::Status;
+
+pub mod progress;
+pub use progress::{Growth, Progress};
+
+pub mod effects;
+
+pub mod element;
+pub use element::{Element, Resistances};
+
+pub mod equipment;
+pub use equipment::{Equipment, Gear, Slot};
+
+pub mod skill;
+pub use skill::{Skill, SkillSlot, Target};
+
+pub mod skills;
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use super::BasicStat;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    pub level: u32,
+    pub xp: u64,
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self { level: 1, xp: 0 }
+    }
+}
+
+impl Progress {
+    pub fn xp_to_next(&self) -> u64 {
+        (20. * (self.level as f64).powf(1.5)).round() as u64
+    }
+
+    // returns how many levels were gained
+    pub fn add_xp(&mut self, xp: u64) -> u32 {
+        self.xp += xp;
+        let mut gained = 0;
+        while self.xp >= self.xp_to_next() {
+            self.xp -= self.xp_to_next();
+            self.level += 1;
+            gained += 1;
+        }
+        gained
+    }
+}
+
+// per level rates, the total bonus at level n is rate * (n - 1)^curve
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Growth {
+    pub p_att: f32,
+    pub m_att: f32,
+    pub p_def: f32,
+    pub m_def: f32,
+    pub max_hp: f32,
+    pub max_mp: f32,
+    pub spd: f32,
+    pub curve: f32,
+}
+
+impl Default for Growth {
+    fn default() -> Self {
+        Self {
+            p_att: 0.,
+            m_att: 0.,
+            p_def: 0.,
+            m_def: 0.,
+            max_hp: 0.,
+            max_mp: 0.,
+            spd: 0.,
+            curve: 1.,
+        }
+    }
+}
+
+fn grow_pool(curr: &mut u32, max: &mut u32, gain: i32) {
+    let old_max = (*max).max(1) as u64;
+    *max = (*max as i64 + gain as i64).max(1) as u32;
+    *curr = (*curr as u64 * *max as u64 / old_max) as u32;
+}
+
+impl Growth {
+    fn bonus(&self, rate: f32, level: u32) -> i32 {
+        (rate * (level.saturating_sub(1) as f32).powf(self.curve)).round() as i32
+    }
+
+    fn gain(&self, rate: f32, level: u32) -> i32 {
+        self.bonus(rate, level) - self.bonus(rate, level - 1)
+    }
+
+    // grows the stat from level - 1 to level, returns the non zero gains
+    pub fn level_up(&self, stat: &mut BasicStat, level: u32) -> Vec<(&'static str, i32)> {
+        let gains = [
+            ("hp", self.gain(self.max_hp, level)),
+            ("mp", self.gain(self.max_mp, level)),
+            ("p_att", self.gain(self.p_att, level)),
+            ("m_att", self.gain(self.m_att, level)),
+            ("p_def", self.gain(self.p_def, level)),
+            ("m_def", self.gain(self.m_def, level)),
+            ("spd", self.gain(self.spd, level)),
+        ];
+        // keep the same ratios, so bigger pools also refill
+        grow_pool(&mut stat.curr_hp, &mut stat.max_hp, gains[0].1);
+        grow_pool(&mut stat.curr_mp, &mut stat.max_mp, gains[1].1);
+        stat.p_att += gains[2].1;
+        stat.m_att += gains[3].1;
+        stat.p_def += gains[4].1;
+        stat.m_def += gains[5].1;
+        stat.spd += gains[6].1;
+        gains.into_iter().filter(|(_, gain)| *gain != 0).collect()
+    }
+}
+
+#[cfg(test)]
+fn test_growth() -> Growth {
+    Growth {
+        p_att: 2.,
+        m_att: 0.5,
+        p_def: 1.,
+        m_def: 0.,
+        max_hp: 10.,
+        max_mp: 0.,
+        spd: 0.,
+        curve: 1.,
+    }
+}
+
+#[test]
+fn test_add_xp_levels_up_repeatedly() {
+    let mut progress = Progress::default();
+    assert_eq!(progress.xp_to_next(), 20);
+    assert_eq!(progress.add_xp(19), 0);
+    assert_eq!(progress.add_xp(1), 1);
+    assert_eq!(progress.level, 2);
+    assert_eq!(progress.xp, 0);
+    // 57 for level 3, 104 for level 4
+    assert_eq!(progress.add_xp(57 + 104 + 5), 2);
+    assert_eq!(progress.level, 4);
+    assert_eq!(progress.xp, 5);
+}
+
+#[test]
+fn test_level_up_grows_stats_and_heals_proportionally() {
+    let mut stat = BasicStat {
+        curr_hp: 5,
+        max_hp: 10,
+        ..Default::default()
+    };
+    let gains = test_growth().level_up(&mut stat, 2);
+    assert_eq!(gains, vec![("hp", 10), ("p_att", 2), ("m_att", 1), ("p_def", 1)]);
+    assert_eq!((stat.curr_hp, stat.max_hp), (10, 20));
+    assert_eq!(stat.p_att, 17);
+    // fractional rates add up over levels
+    let gains = test_growth().level_up(&mut stat, 3);
+    assert!(!gains.iter().any(|(name, _)| *name == "m_att"));
+}
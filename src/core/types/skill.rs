@@ -0,0 +1,110 @@
+use std::fmt::{Debug, Display};
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use super::{BasicStat, Status};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Target {
+    Enemy,
+    AllEnemies,
+    Ally,
+    AllAllies,
+    Myself,
+}
+
+impl Target {
+    pub fn is_offensive(&self) -> bool {
+        matches!(self, Target::Enemy | Target::AllEnemies)
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Target::Enemy => write!(f, "enemy"),
+            Target::AllEnemies => write!(f, "all enemies"),
+            Target::Ally => write!(f, "ally"),
+            Target::AllAllies => write!(f, "all allies"),
+            Target::Myself => write!(f, "self"),
+        }
+    }
+}
+
+// like Effect hooks, the messages are meant to be prefixed with the target's name
+#[typetag::serde(tag = "type", content = "value")]
+pub trait Skill: Debug {
+    fn name(&self) -> &str;
+    fn target(&self) -> Target;
+
+    // mana
+    fn cost(&self) -> u32 {
+        0
+    }
+
+    // in the caster's own turns
+    fn cooldown(&self) -> u32 {
+        0
+    }
+
+    fn heals(&self) -> bool {
+        false
+    }
+
+    // only works on the dead
+    fn revives(&self) -> bool {
+        false
+    }
+
+    // rough strength used to rank skills, the damage or healing multiplier
+    fn power(&self) -> f32 {
+        0.
+    }
+
+    // what happens to a single target, given the caster's effective stats.
+    // hits can miss or crit, the roll comes from the world's rng
+    fn use_on(
+        &self,
+        caster: &BasicStat,
+        target: &mut BasicStat,
+        status: &mut Status,
+        rng: &mut dyn RngCore,
+    ) -> Vec<String>;
+
+    fn boxed_clone(&self) -> Box<dyn Skill>;
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillSlot {
+    pub skill: Box<dyn Skill>,
+    pub ready_in: u32,
+}
+
+impl SkillSlot {
+    pub fn new(skill: Box<dyn Skill>) -> Self {
+        Self { skill, ready_in: 0 }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready_in == 0
+    }
+
+    pub fn usable_by(&self, stat: &BasicStat) -> bool {
+        self.is_ready() && stat.curr_mp >= self.skill.cost()
+    }
+}
+
+impl Display for SkillSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}", self.skill.name(), self.skill.target())?;
+        if self.skill.cost() > 0 {
+            write!(f, ", {}mp", self.skill.cost())?;
+        }
+        if self.is_ready() {
+            write!(f, ", ready)")
+        } else {
+            write!(f, ", ready in {})", self.ready_in)
+        }
+    }
+}
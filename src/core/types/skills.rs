@@ -0,0 +1,374 @@
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::core::combat::{hit_damage, notes, roll, DamageKind};
+
+use super::{
+    skill::{Skill, Target},
+    status::Effect,
+    BasicStat, Element, Status,
+};
+
+fn clone_effect(effect: &Option<Box<dyn Effect>>) -> Option<Box<dyn Effect>> {
+    effect.as_ref().map(|e| e.boxed_clone())
+}
+
+fn apply(effect: &Option<Box<dyn Effect>>, target: &mut BasicStat, status: &mut Status) -> Vec<String> {
+    match clone_effect(effect) {
+        Some(effect) if status.is_alive() => status.apply_effect(effect, target),
+        _ => Vec::new(),
+    }
+}
+
+// damage scaled from one attack stat, optionally elemental and leaving an effect behind
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Strike {
+    pub name: String,
+    pub kind: DamageKind,
+    #[serde(default)]
+    pub element: Option<Element>,
+    pub power: f32,
+    pub target: Target,
+    #[serde(default)]
+    pub cost: u32,
+    #[serde(default)]
+    pub cooldown: u32,
+    #[serde(default)]
+    pub effect: Option<Box<dyn Effect>>,
+}
+
+#[typetag::serde]
+impl Skill for Strike {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn target(&self) -> Target {
+        self.target
+    }
+
+    fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    fn cooldown(&self) -> u32 {
+        self.cooldown
+    }
+
+    fn power(&self) -> f32 {
+        self.power
+    }
+
+    fn use_on(
+        &self,
+        caster: &BasicStat,
+        target: &mut BasicStat,
+        status: &mut Status,
+        rng: &mut dyn RngCore,
+    ) -> Vec<String> {
+        let mut scaled = caster.clone();
+        match self.kind {
+            DamageKind::Physical => scaled.p_att = (scaled.p_att as f32 * self.power).round() as i32,
+            DamageKind::Magical => scaled.m_att = (scaled.m_att as f32 * self.power).round() as i32,
+        }
+        let defender = status.modified(target);
+        let hit = roll(&scaled, &defender, rng);
+        if hit.missed {
+            return vec![format!("evades {}: MISS", self.name)];
+        }
+        let raw = hit_damage(&scaled, &defender, self.kind, self.element, hit);
+        let dealt = status.absorb(raw);
+        target.curr_hp = target.curr_hp.saturating_sub(dealt);
+        status.check_alive(target);
+        let mut notes = notes(&defender, self.element, hit);
+        if raw > dealt {
+            notes.push(format!("{} absorbed", raw - dealt));
+        }
+        // elemental hits are named after the element
+        let kind = self.element.map_or(self.kind.to_string(), |e| e.to_string());
+        let mut lines = vec![if notes.is_empty() {
+            format!("takes {} {} damage", dealt, kind)
+        } else {
+            format!("takes {} {} damage ({})", dealt, kind, notes.join(", "))
+        }];
+        lines.extend(apply(&self.effect, target, status));
+        lines
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Skill> {
+        Box::new(Strike {
+            name: self.name.clone(),
+            effect: clone_effect(&self.effect),
+            ..*self
+        })
+    }
+}
+
+// heals from magical attack
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Mend {
+    pub name: String,
+    pub power: f32,
+    pub target: Target,
+    #[serde(default)]
+    pub cost: u32,
+    #[serde(default)]
+    pub cooldown: u32,
+    #[serde(default)]
+    pub effect: Option<Box<dyn Effect>>,
+}
+
+#[typetag::serde]
+impl Skill for Mend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn target(&self) -> Target {
+        self.target
+    }
+
+    fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    fn cooldown(&self) -> u32 {
+        self.cooldown
+    }
+
+    fn heals(&self) -> bool {
+        true
+    }
+
+    fn power(&self) -> f32 {
+        self.power
+    }
+
+    fn use_on(
+        &self,
+        caster: &BasicStat,
+        target: &mut BasicStat,
+        status: &mut Status,
+        _rng: &mut dyn RngCore,
+    ) -> Vec<String> {
+        if !status.is_alive() {
+            return Vec::new();
+        }
+        let amount = ((caster.m_att.max(0) as f32 * self.power).round() as u32)
+            .min(target.max_hp.saturating_sub(target.curr_hp));
+        target.curr_hp += amount;
+        let mut lines = vec![format!("recovers {} hp", amount)];
+        lines.extend(apply(&self.effect, target, status));
+        lines
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Skill> {
+        Box::new(Mend {
+            name: self.name.clone(),
+            effect: clone_effect(&self.effect),
+            ..*self
+        })
+    }
+}
+
+// brings a fallen ally back with a share of their max hp
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revive {
+    pub name: String,
+    pub power: f32,
+    pub target: Target,
+    #[serde(default)]
+    pub cost: u32,
+    #[serde(default)]
+    pub cooldown: u32,
+}
+
+#[typetag::serde]
+impl Skill for Revive {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn target(&self) -> Target {
+        self.target
+    }
+
+    fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    fn cooldown(&self) -> u32 {
+        self.cooldown
+    }
+
+    fn revives(&self) -> bool {
+        true
+    }
+
+    fn power(&self) -> f32 {
+        self.power
+    }
+
+    fn use_on(
+        &self,
+        _caster: &BasicStat,
+        target: &mut BasicStat,
+        status: &mut Status,
+        _rng: &mut dyn RngCore,
+    ) -> Vec<String> {
+        if status.is_alive() {
+            return Vec::new();
+        }
+        target.curr_hp = ((target.max_hp as f32 * self.power).round() as u32).clamp(1, target.max_hp.max(1));
+        status.revive();
+        vec![format!("gets back up with {} hp", target.curr_hp)]
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Skill> {
+        Box::new(self.clone())
+    }
+}
+
+// only applies its effect, for buffs and curses
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Enchant {
+    pub name: String,
+    pub target: Target,
+    pub effect: Box<dyn Effect>,
+    #[serde(default)]
+    pub cost: u32,
+    #[serde(default)]
+    pub cooldown: u32,
+}
+
+#[typetag::serde]
+impl Skill for Enchant {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn target(&self) -> Target {
+        self.target
+    }
+
+    fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    fn cooldown(&self) -> u32 {
+        self.cooldown
+    }
+
+    fn use_on(
+        &self,
+        _caster: &BasicStat,
+        target: &mut BasicStat,
+        status: &mut Status,
+        _rng: &mut dyn RngCore,
+    ) -> Vec<String> {
+        if !status.is_alive() {
+            return Vec::new();
+        }
+        status.apply_effect(self.effect.boxed_clone(), target)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Skill> {
+        Box::new(Enchant {
+            name: self.name.clone(),
+            effect: self.effect.boxed_clone(),
+            ..*self
+        })
+    }
+}
+
+#[cfg(test)]
+use crate::core::world::test_rng;
+
+#[cfg(test)]
+use super::effects::{Burn, Shield};
+
+#[test]
+fn test_strike_scales_and_applies_effect() {
+    let caster = BasicStat::default();
+    let fireball = Strike {
+        name: "Fireball".to_owned(),
+        kind: DamageKind::Magical,
+        element: None,
+        power: 2.,
+        target: Target::AllEnemies,
+        cost: 5,
+        cooldown: 2,
+        effect: Some(Box::new(Burn { damage: 2, duration: 2 })),
+    };
+    let mut target = BasicStat::default();
+    let mut status = Status::default();
+    let lines = fireball.use_on(&caster, &mut target, &mut status, &mut test_rng());
+    // 30^2 / (30 + 15)
+    assert_eq!(target.curr_hp, 0);
+    assert_eq!(lines, vec!["takes 20 magical damage"]);
+    assert!(!status.is_alive());
+    assert!(status.effects().is_empty());
+
+    let mut target = BasicStat {
+        curr_hp: 50,
+        max_hp: 50,
+        ..Default::default()
+    };
+    let mut status = Status::default();
+    let lines = fireball.boxed_clone().use_on(&caster, &mut target, &mut status, &mut test_rng());
+    assert_eq!(target.curr_hp, 30);
+    assert_eq!(lines, vec!["takes 20 magical damage", "catches fire"]);
+}
+
+#[test]
+fn test_mend_and_enchant() {
+    let caster = BasicStat::default();
+    let mut target = BasicStat {
+        curr_hp: 1,
+        ..Default::default()
+    };
+    let mut status = Status::default();
+    let heal = Mend {
+        name: "Heal".to_owned(),
+        power: 0.5,
+        target: Target::Ally,
+        cost: 0,
+        cooldown: 0,
+        effect: None,
+    };
+    assert_eq!(heal.use_on(&caster, &mut target, &mut status, &mut test_rng()), vec!["recovers 8 hp"]);
+    assert_eq!(heal.use_on(&caster, &mut target, &mut status, &mut test_rng()), vec!["recovers 6 hp"]);
+
+    let guard = Enchant {
+        name: "Guard".to_owned(),
+        target: Target::Myself,
+        effect: Box::new(Shield { amount: 5, duration: 2 }),
+        cost: 0,
+        cooldown: 3,
+    };
+    guard.use_on(&caster, &mut target, &mut status, &mut test_rng());
+    assert_eq!(status.effects()[0].name(), "Shield");
+}
+
+#[test]
+fn test_revive_only_works_on_the_dead() {
+    let caster = BasicStat::default();
+    let revive = Revive {
+        name: "Revive".to_owned(),
+        power: 0.5,
+        target: Target::Ally,
+        cost: 0,
+        cooldown: 0,
+    };
+    let mut target = BasicStat::default();
+    let mut status = Status::default();
+    assert!(revive.use_on(&caster, &mut target, &mut status, &mut test_rng()).is_empty());
+    assert_eq!(target.curr_hp, 15);
+
+    target.curr_hp = 0;
+    status.apply_effect(Box::new(Burn { damage: 2, duration: 2 }), &mut target);
+    status.set_alive(false);
+    assert_eq!(revive.use_on(&caster, &mut target, &mut status, &mut test_rng()), vec!["gets back up with 8 hp"]);
+    assert!(status.is_alive());
+    assert!(status.effects().is_empty());
+}
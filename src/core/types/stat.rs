@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use super::Resistances;
+
+// missing fields fall back to the defaults, so data files only list what they change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct BasicStat {
     pub p_att: i32,
     pub m_att: i32,
@@ -8,7 +12,16 @@ pub struct BasicStat {
     pub m_def: i32,
     pub curr_hp: u32,
     pub max_hp: u32,
+    pub curr_mp: u32,
+    pub max_mp: u32,
     pub spd: i32,
+    // secondary stats, all in percent. the defaults never miss and never crit
+    pub crit_chance: i32,
+    // extra damage on a crit
+    pub crit_damage: i32,
+    pub accuracy: i32,
+    pub evasion: i32,
+    pub resistances: Resistances,
 }
 
 impl Default for BasicStat {
@@ -20,7 +33,14 @@ impl Default for BasicStat {
             m_def: 15,
             curr_hp: 15,
             max_hp: 15,
+            curr_mp: 15,
+            max_mp: 15,
             spd: 15,
+            crit_chance: 0,
+            crit_damage: 50,
+            accuracy: 100,
+            evasion: 0,
+            resistances: Resistances::default(),
         }
     }
 }
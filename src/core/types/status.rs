@@ -2,7 +2,9 @@ use std::fmt::Debug;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+use super::{BasicStat, Element};
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Status {
     alive: bool,
     efects: Vec<Box<dyn Effect>>,
@@ -70,22 +72,143 @@ pub struct Status {
     //intangible_to_all_except_physical_magical_and_elemental_and_status: bool,
 }
 
-// it's just a concept for now, because i currently have no idea how to implement this to affect
-// the battle
+impl Default for Status {
+    fn default() -> Self {
+        Self {
+            alive: true,
+            efects: Vec::new(),
+        }
+    }
+}
+
+impl Status {
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+
+    pub fn set_alive(&mut self, alive: bool) {
+        self.alive = alive;
+    }
+
+    // back from the dead with a clean slate
+    pub fn revive(&mut self) {
+        self.alive = true;
+        self.efects.clear();
+    }
+
+    pub fn effects(&self) -> &[Box<dyn Effect>] {
+        &self.efects
+    }
+
+    // applying an effect that is already active refreshes it instead of stacking,
+    // being immune to its element keeps it off entirely
+    pub fn apply_effect(&mut self, mut effect: Box<dyn Effect>, stat: &mut BasicStat) -> Vec<String> {
+        if effect.element().is_some_and(|e| stat.resistances.is_immune(e)) {
+            return vec![format!("is immune to {}", effect.name().to_lowercase())];
+        }
+        let mut messages = Vec::new();
+        if let Some(idx) = self.efects.iter().position(|e| e.name() == effect.name()) {
+            let mut old = self.efects.remove(idx);
+            messages.extend(old.on_expire(stat));
+        }
+        messages.extend(effect.on_apply(stat));
+        self.efects.push(effect);
+        self.check_alive(stat);
+        messages
+    }
+
+    pub fn turn_start(&mut self, stat: &mut BasicStat) -> Vec<String> {
+        let messages = self
+            .efects
+            .iter_mut()
+            .filter_map(|e| e.on_turn_start(stat))
+            .collect();
+        self.check_alive(stat);
+        messages
+    }
+
+    pub fn turn_end(&mut self, stat: &mut BasicStat) -> Vec<String> {
+        let mut messages: Vec<String> = self
+            .efects
+            .iter_mut()
+            .filter_map(|e| e.on_turn_end(stat))
+            .collect();
+        for effect in self.efects.iter_mut() {
+            effect.set_duration(effect.duration() - 1);
+        }
+        let (expired, active) = std::mem::take(&mut self.efects)
+            .into_iter()
+            .partition::<Vec<_>, _>(|e| e.duration() <= 0);
+        self.efects = active;
+        for mut effect in expired {
+            messages.extend(effect.on_expire(stat));
+        }
+        self.check_alive(stat);
+        messages
+    }
+
+    pub fn can_act(&self) -> bool {
+        self.alive && self.efects.iter().all(|e| !e.prevents_action())
+    }
+
+    pub fn absorb(&mut self, damage: u32) -> u32 {
+        self.efects
+            .iter_mut()
+            .fold(damage, |damage, e| e.absorb(damage))
+    }
 
+    pub fn modified(&self, stat: &BasicStat) -> BasicStat {
+        let mut stat = stat.clone();
+        for effect in self.efects.iter() {
+            effect.modify_stat(&mut stat);
+        }
+        stat
+    }
+
+    pub fn check_alive(&mut self, stat: &BasicStat) {
+        if stat.curr_hp == 0 {
+            self.alive = false;
+        }
+    }
+}
+
+// hooks return an optional message meant to be prefixed with the owner's name in the log
 #[typetag::serde(tag = "type", content = "value")]
 pub trait Effect: Debug {
+    fn name(&self) -> &str;
+    fn duration(&self) -> i32;
+    fn set_duration(&mut self, duration: i32);
+    // skills hand out a fresh copy of their effect to every target
+    fn boxed_clone(&self) -> Box<dyn Effect>;
 
-}
+    // burns are fire, poison is poison, the immune never get them
+    fn element(&self) -> Option<Element> {
+        None
+    }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DragonPoison {
-    pub damage: i32,
-    pub duration: i32,
-}
+    fn on_apply(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        None
+    }
 
-#[typetag::serde]
-impl Effect for DragonPoison {
-}
+    fn on_turn_start(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        None
+    }
+
+    fn on_turn_end(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        None
+    }
+
+    fn on_expire(&mut self, _stat: &mut BasicStat) -> Option<String> {
+        None
+    }
 
+    fn modify_stat(&self, _stat: &mut BasicStat) {}
 
+    fn prevents_action(&self) -> bool {
+        false
+    }
+
+    fn absorb(&mut self, damage: u32) -> u32 {
+        damage
+    }
+}
@@ -0,0 +1,194 @@
+use std::{
+    cell::{RefCell, RefMut},
+    collections::BTreeMap,
+    rc::Rc,
+};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::entities::{enemy::Enemy, hero::Hero, Entity, EntityType, Id, SharedEntity};
+
+pub type SharedWorld = Rc<RefCell<World>>;
+// a stream that can be saved as a seed and a position and picked up again
+pub type GameRng = ChaCha8Rng;
+
+// the one place heroes and enemies live, everything else keeps ids and looks them up here.
+// each entity sits in its own cell so combat can hold the actor and its target at the same time
+#[derive(Debug)]
+pub struct World {
+    heroes: BTreeMap<Id, Rc<RefCell<Hero>>>,
+    enemies: BTreeMap<Id, Rc<RefCell<Enemy>>>,
+    seed: u64,
+    // every random decision in a run draws from here, so the same seed plays out the same way
+    rng: RefCell<GameRng>,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::seeded(0)
+    }
+}
+
+// the sides an EntityList can show
+pub trait Member: Entity + Sized {
+    fn roster(world: &World) -> Vec<Rc<RefCell<Self>>>;
+}
+
+impl Member for Hero {
+    fn roster(world: &World) -> Vec<Rc<RefCell<Self>>> {
+        world.heroes()
+    }
+}
+
+impl Member for Enemy {
+    fn roster(world: &World) -> Vec<Rc<RefCell<Self>>> {
+        world.enemies()
+    }
+}
+
+impl World {
+    pub fn shared() -> SharedWorld {
+        Rc::new(RefCell::new(World::default()))
+    }
+
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            heroes: BTreeMap::new(),
+            enemies: BTreeMap::new(),
+            seed,
+            rng: RefCell::new(GameRng::seed_from_u64(seed)),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn rng(&self) -> RefMut<'_, GameRng> {
+        self.rng.borrow_mut()
+    }
+
+    // how far into the seed's stream the run is, saved next to the seed
+    pub fn rng_position(&self) -> u128 {
+        self.rng.borrow().get_word_pos()
+    }
+
+    pub fn reseed(&mut self, seed: u64, position: u128) {
+        let mut rng = GameRng::seed_from_u64(seed);
+        rng.set_word_pos(position);
+        self.seed = seed;
+        self.rng = RefCell::new(rng);
+    }
+
+    pub fn add_hero(&mut self, hero: Hero) {
+        self.heroes.insert(hero.id, Rc::new(RefCell::new(hero)));
+    }
+
+    pub fn add_enemy(&mut self, enemy: Enemy) {
+        self.enemies.insert(enemy.id(), Rc::new(RefCell::new(enemy)));
+    }
+
+    // a new wave replaces the last one
+    pub fn set_enemies(&mut self, enemies: Vec<Enemy>) {
+        self.enemies.clear();
+        for enemy in enemies {
+            self.add_enemy(enemy);
+        }
+    }
+
+    // everything at once, from a save
+    pub fn replace(&mut self, heroes: Vec<Rc<RefCell<Hero>>>, enemies: Vec<Rc<RefCell<Enemy>>>) {
+        self.heroes = heroes.into_iter().map(|h| (h.borrow().id, h.clone())).collect();
+        self.enemies = enemies.into_iter().map(|e| (e.borrow().id(), e.clone())).collect();
+    }
+
+    pub fn hero(&self, id: Id) -> Option<Rc<RefCell<Hero>>> {
+        self.heroes.get(&id).cloned()
+    }
+
+    pub fn get(&self, id: Id) -> Option<SharedEntity> {
+        self.heroes
+            .get(&id)
+            .map(|h| h.clone() as SharedEntity)
+            .or_else(|| self.enemies.get(&id).map(|e| e.clone() as SharedEntity))
+    }
+
+    pub fn is_alive(&self, id: Id) -> bool {
+        self.get(id).is_some_and(|e| e.borrow().is_alive())
+    }
+
+    // in the order they joined, ids only ever go up
+    pub fn heroes(&self) -> Vec<Rc<RefCell<Hero>>> {
+        self.heroes.values().cloned().collect()
+    }
+
+    pub fn enemies(&self) -> Vec<Rc<RefCell<Enemy>>> {
+        self.enemies.values().cloned().collect()
+    }
+
+    pub fn heroes_dyn(&self) -> Vec<SharedEntity> {
+        self.heroes.values().map(|e| e.clone() as SharedEntity).collect()
+    }
+
+    pub fn enemies_dyn(&self) -> Vec<SharedEntity> {
+        self.enemies.values().map(|e| e.clone() as SharedEntity).collect()
+    }
+
+    // allies and opponents of an entity of that type
+    pub fn sides(&self, of: EntityType) -> (Vec<SharedEntity>, Vec<SharedEntity>) {
+        if of.is_party() {
+            (self.heroes_dyn(), self.enemies_dyn())
+        } else {
+            (self.enemies_dyn(), self.heroes_dyn())
+        }
+    }
+
+    pub fn ids(&self) -> Vec<Id> {
+        self.heroes.keys().chain(self.enemies.keys()).copied().collect()
+    }
+}
+
+#[test]
+fn test_world_is_keyed_by_id() {
+    let mut world = World::default();
+    let hero = Hero::new();
+    let hero_id = hero.id;
+    let enemies = vec![Enemy::new(), Enemy::new()];
+    let enemy_ids: Vec<Id> = enemies.iter().map(|e| e.id()).collect();
+    world.add_hero(hero);
+    world.set_enemies(enemies);
+    assert_eq!(world.ids(), [vec![hero_id], enemy_ids.clone()].concat());
+
+    // every handle points at the same entity
+    world.get(enemy_ids[0]).unwrap().borrow_mut().stat_and_status_mut().1.set_alive(false);
+    assert!(!world.enemies()[0].borrow().is_alive());
+    assert!(!world.is_alive(enemy_ids[0]));
+    assert!(world.is_alive(hero_id));
+    assert!(world.get(0).is_none());
+
+    world.set_enemies(vec![Enemy::new()]);
+    assert!(world.get(enemy_ids[1]).is_none());
+    assert_eq!(world.hero(hero_id).unwrap().borrow().id, hero_id);
+}
+
+#[cfg(test)]
+pub fn test_rng() -> GameRng {
+    GameRng::seed_from_u64(0)
+}
+
+#[test]
+fn test_rng_picks_up_where_it_left_off() {
+    use rand::Rng;
+
+    let world = World::seeded(7);
+    let first: Vec<u32> = (0..5).map(|_| world.rng().gen()).collect();
+    let position = world.rng_position();
+    let next: Vec<u32> = (0..5).map(|_| world.rng().gen()).collect();
+
+    let mut replay = World::default();
+    replay.reseed(7, 0);
+    assert_eq!((0..5).map(|_| replay.rng().gen()).collect::<Vec<u32>>(), first);
+    replay.reseed(world.seed(), position);
+    assert_eq!((0..5).map(|_| replay.rng().gen()).collect::<Vec<u32>>(), next);
+}
@@ -1,9 +1,12 @@
+use std::time::Duration;
+
 use anyhow::Result;
 use context::Config;
 use ratatui::layout::Rect;
 
 mod core;
-use core::App;
+use core::{App, Catalog};
+pub use core::sim;
 
 mod event;
 pub use event::Event;
@@ -11,6 +14,9 @@ pub use event::Event;
 mod context;
 pub use context::Context;
 
+#[cfg(test)]
+mod testing;
+
 cfg_if::cfg_if! {
     if #[cfg(target_arch = "wasm32")] {
         pub async fn run() -> Result<()> {
@@ -23,15 +29,48 @@ cfg_if::cfg_if! {
     } else  {
         mod backends;
         use backends::Crossterm;
+        use anyhow::{anyhow, bail, Context as _};
+
+        const USAGE: &str = "usage: idle-termquest [--seed N] [--battle-pause SECS] [--manual-timeout SECS] [--history-len N]";
+
+        // flags win over config.json
+        fn apply_args(config: &mut Config, mut args: impl Iterator<Item = String>) -> Result<()> {
+            while let Some(arg) = args.next() {
+                let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", arg));
+                match arg.as_str() {
+                    "--seed" => {
+                        let value = value()?;
+                        config.seed = Some(value.parse().with_context(|| format!("bad seed '{}'", value))?);
+                    }
+                    "--battle-pause" => config.battle_pause = secs_arg(&value()?)?,
+                    "--manual-timeout" => config.manual_timeout = secs_arg(&value()?)?,
+                    "--history-len" => {
+                        let value = value()?;
+                        config.history_len = value.parse().with_context(|| format!("bad history length '{}'", value))?;
+                    }
+                    _ => bail!("unknown option '{}', {}", arg, USAGE),
+                }
+            }
+            Ok(())
+        }
+
+        fn secs_arg(value: &str) -> Result<Duration> {
+            value
+                .parse()
+                .ok()
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                .ok_or_else(|| anyhow!("bad duration '{}', expected seconds", value))
+        }
 
         pub async fn run() -> Result<()> {
             let mut app = App::new();
 
+            let mut config = Config::load_from(&Config::file())?;
+            apply_args(&mut config, std::env::args().skip(1))?;
             let mut ctx = Context::default()
-                .config(Config {
-                    tick_rate: 24.,
-                    frame_rate: 24.
-                });
+                .config(config)
+                // bad data files should fail here, before the terminal is taken over
+                .catalog(Catalog::load()?);
 
             let mut backend = Crossterm::new()?
                 .tick_rate(ctx.config.tick_rate)
@@ -46,7 +85,7 @@ cfg_if::cfg_if! {
             loop {
                 if let Some(event) = backend.next().await {
                     match event {
-                        Event::Init => app.init()?,
+                        Event::Init => app.init(&ctx)?,
                         Event::Render => { backend.draw(|frame| app.render(frame, &ctx))?; },
                         Event::Resize(w, h) => {
                             backend.terminal.resize(Rect::new(0, 0, w, h))?;